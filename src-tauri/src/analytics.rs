@@ -0,0 +1,696 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+use crate::study_stats::read_study_goal;
+use crate::word_bank::{read_session_profile, read_study_clock, Database};
+
+const DEFAULT_DAYS: i64 = 30;
+const MAX_DAYS: i64 = 365;
+const DEFAULT_HARD_WORDS: i64 = 20;
+const MAX_HARD_WORDS: i64 = 200;
+/// Review gaps in days, `[from, to)`; the last bucket is open-ended.
+const RETENTION_BUCKETS: [(f64, Option<f64>); 7] = [
+    (0.0, Some(1.0)),
+    (1.0, Some(3.0)),
+    (3.0, Some(7.0)),
+    (7.0, Some(14.0)),
+    (14.0, Some(30.0)),
+    (30.0, Some(60.0)),
+    (60.0, None),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionBucket {
+    pub min_days: f64,
+    pub max_days: Option<f64>,
+    pub reviews: i64,
+    pub retained: i64,
+    /// `None` while the bucket has no reviews yet.
+    pub retention: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MasteryPoint {
+    pub day: String,
+    pub unseen: i64,
+    pub low: i64,
+    pub mid: i64,
+    pub high: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MasterySeries {
+    pub word_list_id: i64,
+    pub name: String,
+    pub points: Vec<MasteryPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HardWord {
+    pub word_id: i64,
+    pub word: String,
+    pub misses: i64,
+    pub attempts: i64,
+    pub miss_rate: f64,
+    pub proficiency_score: Option<i64>,
+    pub last_missed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyPoint {
+    pub day: String,
+    pub answers: i64,
+    pub average_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySeries {
+    pub answers: i64,
+    pub average_ms: Option<f64>,
+    pub points: Vec<LatencyPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastDay {
+    pub day: String,
+    /// Reviews falling due that day; the first day also carries overdue ones.
+    pub due: i64,
+    /// Unseen words the daily new word goal brings in that day. Sessions take
+    /// new words from the active list, so only that list gets any.
+    pub new_words: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListForecast {
+    pub word_list_id: i64,
+    pub name: String,
+    pub total_due: i64,
+    pub total_new_words: i64,
+    pub days: Vec<ForecastDay>,
+}
+
+fn day_count(days: Option<i64>) -> Result<i64> {
+    let days = days.unwrap_or(DEFAULT_DAYS);
+    if !(1..=MAX_DAYS).contains(&days) {
+        bail!("天数需在 1 到 {MAX_DAYS} 之间");
+    }
+    Ok(days)
+}
+
+/// Study days `today + start .. today + start + count`, oldest first.
+pub(crate) async fn study_day_range(
+    pool: &SqlitePool,
+    day_modifier: &str,
+    start: i64,
+    count: i64,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar(
+        r#"
+WITH RECURSIVE offsets(n) AS (
+  SELECT ?
+  UNION ALL
+  SELECT n + 1 FROM offsets WHERE n + 1 < ?
+)
+SELECT date('now', ?, printf('%+d days', n)) FROM offsets ORDER BY n
+"#,
+    )
+    .bind(start)
+    .bind(start + count)
+    .bind(day_modifier)
+    .fetch_all(pool)
+    .await
+    .context("计算学习日期失败")
+}
+
+fn bucket_index(gap_days: f64) -> usize {
+    RETENTION_BUCKETS
+        .iter()
+        .position(|(_, to)| to.is_none_or(|to| gap_days < to))
+        .unwrap_or(RETENTION_BUCKETS.len() - 1)
+}
+
+async fn retention_with_pool(pool: &SqlitePool) -> Result<Vec<RetentionBucket>> {
+    // Only graded rows say whether the word was recalled; the first answer for a
+    // word is learning rather than retention, so it only anchors the next gap.
+    let rows = sqlx::query(
+        r#"
+SELECT word_id, julianday(learned_at) AS learned_day, grade
+FROM study_log
+WHERE grade IS NOT NULL
+ORDER BY word_id, learned_at, id
+"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("读取学习记录失败")?;
+
+    let mut totals = [(0_i64, 0_i64); RETENTION_BUCKETS.len()];
+    let mut previous: Option<(i64, f64)> = None;
+    for row in rows {
+        let word_id: i64 = row.try_get("word_id").context("读取单词 ID 失败")?;
+        let learned_day: f64 = row.try_get("learned_day").context("读取学习时间失败")?;
+        let grade: String = row.try_get("grade").context("读取评分失败")?;
+        if let Some((previous_word, previous_day)) = previous {
+            if previous_word == word_id {
+                let bucket = &mut totals[bucket_index(learned_day - previous_day)];
+                bucket.0 += 1;
+                bucket.1 += i64::from(grade != "again");
+            }
+        }
+        previous = Some((word_id, learned_day));
+    }
+
+    Ok(RETENTION_BUCKETS
+        .iter()
+        .zip(totals)
+        .map(
+            |(&(min_days, max_days), (reviews, retained))| RetentionBucket {
+                min_days,
+                max_days,
+                reviews,
+                retained,
+                retention: (reviews > 0).then(|| retained as f64 / reviews as f64),
+            },
+        )
+        .collect())
+}
+
+/// Score changes of one word, ordered by study day.
+#[derive(Default)]
+struct ScoreTimeline {
+    /// Score before the first logged change, if the word had one.
+    initial: Option<i64>,
+    changes: Vec<(String, i64)>,
+}
+
+impl ScoreTimeline {
+    fn score_on(&self, day: &str, current: Option<i64>) -> Option<i64> {
+        if self.changes.is_empty() {
+            return current;
+        }
+        let seen = self
+            .changes
+            .partition_point(|(change_day, _)| change_day.as_str() <= day);
+        match seen {
+            0 => self.initial,
+            seen => Some(self.changes[seen - 1].1),
+        }
+    }
+}
+
+async fn mastery_history_with_pool(pool: &SqlitePool, days: i64) -> Result<Vec<MasterySeries>> {
+    let profile = read_session_profile(pool).await?;
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let day_range = study_day_range(pool, &day_modifier, 1 - days, days).await?;
+
+    let rows = sqlx::query(
+        r#"
+SELECT word_id, date(learned_at, ?) AS study_day, previous_score, new_score
+FROM study_log
+WHERE new_score IS NOT NULL
+ORDER BY word_id, learned_at, id
+"#,
+    )
+    .bind(&day_modifier)
+    .fetch_all(pool)
+    .await
+    .context("读取熟练度变化失败")?;
+    let mut timelines: HashMap<i64, ScoreTimeline> = HashMap::new();
+    for row in rows {
+        let word_id: i64 = row.try_get("word_id").context("读取单词 ID 失败")?;
+        let study_day: String = row.try_get("study_day").context("读取学习日失败")?;
+        let previous_score: Option<i64> =
+            row.try_get("previous_score").context("读取原熟练度失败")?;
+        let new_score: i64 = row.try_get("new_score").context("读取新熟练度失败")?;
+        let timeline = timelines.entry(word_id).or_default();
+        if timeline.changes.is_empty() {
+            timeline.initial = previous_score;
+        }
+        timeline.changes.push((study_day, new_score));
+    }
+
+    let rows = sqlx::query(
+        r#"
+SELECT wl.id AS word_list_id, wl.name AS name, wlm.word_id AS word_id, uwl.proficiency_score AS score
+FROM word_list wl
+LEFT JOIN word_list_map wlm ON wlm.word_list_id = wl.id
+LEFT JOIN user_word_learning uwl ON uwl.word_id = wlm.word_id
+ORDER BY wl.created_at DESC, wl.id DESC
+"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("读取词库单词失败")?;
+
+    let empty = ScoreTimeline::default();
+    let mut series: Vec<MasterySeries> = Vec::new();
+    for row in rows {
+        let word_list_id: i64 = row.try_get("word_list_id").context("读取词库 ID 失败")?;
+        if series.last().map(|last| last.word_list_id) != Some(word_list_id) {
+            series.push(MasterySeries {
+                word_list_id,
+                name: row.try_get("name").context("读取词库名称失败")?,
+                points: day_range
+                    .iter()
+                    .map(|day| MasteryPoint {
+                        day: day.clone(),
+                        ..MasteryPoint::default()
+                    })
+                    .collect(),
+            });
+        }
+        let Some(word_id) = row
+            .try_get::<Option<i64>, _>("word_id")
+            .context("读取单词 ID 失败")?
+        else {
+            continue;
+        };
+        let current: Option<i64> = row.try_get("score").context("读取熟练度失败")?;
+        let timeline = timelines.get(&word_id).unwrap_or(&empty);
+        let Some(last) = series.last_mut() else {
+            continue;
+        };
+        for point in &mut last.points {
+            match timeline.score_on(&point.day, current) {
+                None => point.unseen += 1,
+                Some(score) if score < profile.low_score_below => point.low += 1,
+                Some(score) if score < profile.high_score_from => point.mid += 1,
+                Some(_) => point.high += 1,
+            }
+        }
+    }
+    Ok(series)
+}
+
+async fn hardest_words_with_pool(pool: &SqlitePool, limit: i64) -> Result<Vec<HardWord>> {
+    let rows = sqlx::query(
+        r#"
+SELECT
+  sl.word_id AS word_id,
+  w.word AS word,
+  COUNT(CASE WHEN sl.grade = 'again' THEN 1 END) AS misses,
+  COUNT(1) AS attempts,
+  MAX(CASE WHEN sl.grade = 'again' THEN sl.learned_at END) AS last_missed_at,
+  uwl.proficiency_score AS proficiency_score
+FROM study_log sl
+JOIN word w ON w.id = sl.word_id
+LEFT JOIN user_word_learning uwl ON uwl.word_id = sl.word_id
+WHERE sl.grade IS NOT NULL
+GROUP BY sl.word_id
+HAVING misses > 0
+ORDER BY misses DESC, CAST(misses AS REAL) / attempts DESC, last_missed_at DESC
+LIMIT ?
+"#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("读取易错单词失败")?;
+
+    let mut words = Vec::with_capacity(rows.len());
+    for row in rows {
+        let misses: i64 = row.try_get("misses").context("读取答错次数失败")?;
+        let attempts: i64 = row.try_get("attempts").context("读取作答次数失败")?;
+        words.push(HardWord {
+            word_id: row.try_get("word_id").context("读取单词 ID 失败")?,
+            word: row.try_get("word").context("读取单词失败")?,
+            misses,
+            attempts,
+            miss_rate: misses as f64 / attempts as f64,
+            proficiency_score: row.try_get("proficiency_score").context("读取熟练度失败")?,
+            last_missed_at: row
+                .try_get("last_missed_at")
+                .context("读取最近答错时间失败")?,
+        });
+    }
+    Ok(words)
+}
+
+async fn answer_latency_with_pool(pool: &SqlitePool, days: i64) -> Result<LatencySeries> {
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let day_range = study_day_range(pool, &day_modifier, 1 - days, days).await?;
+    let first_day = day_range.first().context("计算学习日期失败")?;
+
+    let rows = sqlx::query(
+        r#"
+SELECT date(learned_at, ?) AS study_day, COUNT(1) AS answers, AVG(response_ms) AS average_ms
+FROM study_log
+WHERE response_ms IS NOT NULL AND date(learned_at, ?) >= ?
+GROUP BY study_day
+"#,
+    )
+    .bind(&day_modifier)
+    .bind(&day_modifier)
+    .bind(first_day)
+    .fetch_all(pool)
+    .await
+    .context("读取作答用时失败")?;
+    let mut by_day: HashMap<String, (i64, f64)> = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let day: String = row.try_get("study_day").context("读取学习日失败")?;
+        let answers: i64 = row.try_get("answers").context("读取作答次数失败")?;
+        let average_ms: f64 = row.try_get("average_ms").context("读取平均用时失败")?;
+        by_day.insert(day, (answers, average_ms));
+    }
+
+    let answers: i64 = by_day.values().map(|(answers, _)| answers).sum();
+    let total_ms: f64 = by_day
+        .values()
+        .map(|(answers, average_ms)| *answers as f64 * average_ms)
+        .sum();
+    let points = day_range
+        .into_iter()
+        .map(|day| {
+            let (answers, average_ms) =
+                by_day.get(&day).map_or((0, None), |(answers, average_ms)| {
+                    (*answers, Some(*average_ms))
+                });
+            LatencyPoint {
+                day,
+                answers,
+                average_ms,
+            }
+        })
+        .collect();
+    Ok(LatencySeries {
+        answers,
+        average_ms: (answers > 0).then(|| total_ms / answers as f64),
+        points,
+    })
+}
+
+/// Forecasts due reviews for every list. The daily new word goal is global,
+/// so it is spent once, on the active list, less the new words already
+/// learned today from any list.
+async fn forecast_reviews_with_pool(pool: &SqlitePool, days: i64) -> Result<Vec<ListForecast>> {
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let new_word_target = read_study_goal(pool).await?.new_word_target;
+    let active_list_id: Option<i64> =
+        sqlx::query_scalar("SELECT active_word_list_id FROM word_list_state WHERE id = 1")
+            .fetch_optional(pool)
+            .await
+            .context("读取当前词库失败")?
+            .flatten();
+    let day_range = study_day_range(pool, &day_modifier, 0, days).await?;
+    let (Some(today), Some(last_day)) = (day_range.first(), day_range.last()) else {
+        bail!("天数需在 1 到 {MAX_DAYS} 之间");
+    };
+
+    let rows = sqlx::query(
+        r#"
+SELECT wlm.word_list_id AS word_list_id, MAX(date(uwl.due_at, ?), ?) AS due_day, COUNT(1) AS due
+FROM word_list_map wlm
+JOIN user_word_learning uwl ON uwl.word_id = wlm.word_id
+WHERE uwl.due_at IS NOT NULL AND date(uwl.due_at, ?) <= ?
+GROUP BY wlm.word_list_id, due_day
+"#,
+    )
+    .bind(&day_modifier)
+    .bind(today)
+    .bind(&day_modifier)
+    .bind(last_day)
+    .fetch_all(pool)
+    .await
+    .context("读取待复习单词失败")?;
+    let mut due: HashMap<(i64, String), i64> = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let word_list_id: i64 = row.try_get("word_list_id").context("读取词库 ID 失败")?;
+        let due_day: String = row.try_get("due_day").context("读取到期日期失败")?;
+        let count: i64 = row.try_get("due").context("读取待复习数量失败")?;
+        due.insert((word_list_id, due_day), count);
+    }
+
+    // New words already taken in today count against today's goal, whichever
+    // list they came from.
+    let learned_today: i64 = sqlx::query_scalar(
+        r#"
+SELECT COUNT(1)
+FROM (
+  SELECT MIN(learned_at) AS first_learned_at
+  FROM study_log
+  GROUP BY word_id
+)
+WHERE date(first_learned_at, ?) = ?
+"#,
+    )
+    .bind(&day_modifier)
+    .bind(today)
+    .fetch_one(pool)
+    .await
+    .context("统计今日新词失败")?;
+
+    let rows = sqlx::query(
+        r#"
+SELECT
+  wl.id AS word_list_id,
+  wl.name AS name,
+  COUNT(wlm.word_id) - COUNT(uwl.word_id) AS unseen
+FROM word_list wl
+LEFT JOIN word_list_map wlm ON wlm.word_list_id = wl.id
+LEFT JOIN user_word_learning uwl ON uwl.word_id = wlm.word_id
+GROUP BY wl.id
+ORDER BY wl.created_at DESC, wl.id DESC
+"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("读取词库列表失败")?;
+
+    let mut forecasts = Vec::with_capacity(rows.len());
+    for row in rows {
+        let word_list_id: i64 = row.try_get("word_list_id").context("读取词库 ID 失败")?;
+        let mut unseen: i64 = row.try_get("unseen").context("读取未学单词数量失败")?;
+        let daily_target = if Some(word_list_id) == active_list_id {
+            new_word_target
+        } else {
+            0
+        };
+        let mut quota_used = learned_today;
+        let mut forecast = ListForecast {
+            word_list_id,
+            name: row.try_get("name").context("读取词库名称失败")?,
+            total_due: 0,
+            total_new_words: 0,
+            days: Vec::with_capacity(day_range.len()),
+        };
+        for day in &day_range {
+            let new_words = (daily_target - quota_used).clamp(0, unseen);
+            let day_due = due
+                .get(&(word_list_id, day.clone()))
+                .copied()
+                .unwrap_or_default();
+            unseen -= new_words;
+            quota_used = 0;
+            forecast.total_due += day_due;
+            forecast.total_new_words += new_words;
+            forecast.days.push(ForecastDay {
+                day: day.clone(),
+                due: day_due,
+                new_words,
+            });
+        }
+        forecasts.push(forecast);
+    }
+    Ok(forecasts)
+}
+
+pub async fn forecast_reviews(db: &Database, days: Option<i64>) -> Result<Vec<ListForecast>> {
+    let days = day_count(days)?;
+    let pool = db.pool()?;
+    forecast_reviews_with_pool(&pool, days).await
+}
+
+pub async fn get_retention_by_interval(db: &Database) -> Result<Vec<RetentionBucket>> {
+    let pool = db.pool()?;
+    retention_with_pool(&pool).await
+}
+
+pub async fn get_mastery_history(db: &Database, days: Option<i64>) -> Result<Vec<MasterySeries>> {
+    let days = day_count(days)?;
+    let pool = db.pool()?;
+    mastery_history_with_pool(&pool, days).await
+}
+
+pub async fn list_hardest_words(db: &Database, limit: Option<i64>) -> Result<Vec<HardWord>> {
+    let limit = limit.unwrap_or(DEFAULT_HARD_WORDS).clamp(1, MAX_HARD_WORDS);
+    let pool = db.pool()?;
+    hardest_words_with_pool(&pool, limit).await
+}
+
+pub async fn get_answer_latency(db: &Database, days: Option<i64>) -> Result<LatencySeries> {
+    let days = day_count(days)?;
+    let pool = db.pool()?;
+    answer_latency_with_pool(&pool, days).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word_bank;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use tauri::async_runtime;
+
+    async fn setup_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(SqliteConnectOptions::new().filename(":memory:"))
+            .await
+            .expect("Failed to open test database");
+        word_bank::ensure_schema(&pool)
+            .await
+            .expect("Failed to ensure schema");
+        pool
+    }
+
+    async fn run_script(pool: &SqlitePool, statements: &[&str]) {
+        for statement in statements {
+            sqlx::query(statement)
+                .execute(pool)
+                .await
+                .expect("Failed to prepare analytics data");
+        }
+    }
+
+    #[test]
+    fn retention_and_hardest_words_follow_the_log() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            run_script(
+                &pool,
+                &[
+                    "INSERT INTO word (id, word) VALUES (1, 'alpha'), (2, 'beta')",
+                    "INSERT INTO study_log (word_id, learned_at, grade) VALUES (1, '2024-05-01 09:00:00', 'good'), (1, '2024-05-01 18:00:00', 'again'), (1, '2024-05-03 09:00:00', 'good'), (1, '2024-05-12 09:00:00', 'again')",
+                    "INSERT INTO study_log (word_id, learned_at, grade) VALUES (2, '2024-05-01 09:00:00', 'again'), (2, '2024-05-02 10:00:00', 'good'), (2, '2024-05-02 11:00:00', NULL)",
+                ],
+            )
+            .await;
+
+            let buckets = retention_with_pool(&pool)
+                .await
+                .expect("Failed to read retention");
+            let totals: Vec<(i64, i64)> = buckets
+                .iter()
+                .map(|bucket| (bucket.reviews, bucket.retained))
+                .collect();
+            assert_eq!(
+                totals,
+                [(1, 0), (2, 2), (0, 0), (1, 0), (0, 0), (0, 0), (0, 0)]
+            );
+            assert_eq!(buckets[1].retention, Some(1.0));
+            assert_eq!(buckets[2].retention, None);
+
+            let hardest = hardest_words_with_pool(&pool, 10)
+                .await
+                .expect("Failed to read hardest words");
+            let summary: Vec<(&str, i64, i64)> = hardest
+                .iter()
+                .map(|word| (word.word.as_str(), word.misses, word.attempts))
+                .collect();
+            assert_eq!(summary, [("alpha", 2, 4), ("beta", 1, 2)]);
+            assert_eq!(
+                hardest[0].last_missed_at.as_deref(),
+                Some("2024-05-12 09:00:00")
+            );
+        });
+    }
+
+    #[test]
+    fn mastery_and_latency_series_cover_each_day() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            run_script(
+                &pool,
+                &[
+                    "INSERT INTO word (id, word) VALUES (1, 'alpha'), (2, 'beta'), (3, 'gamma')",
+                    "INSERT INTO word_list (id, name) VALUES (1, 'CET-4')",
+                    "INSERT INTO word_list_map (word_list_id, word_id) VALUES (1, 1), (1, 2), (1, 3)",
+                    "INSERT INTO user_word_learning (word_id, proficiency_score, learn_count) VALUES (1, 9, 3), (2, 5, 1)",
+                    "INSERT INTO study_log (word_id, learned_at, grade, previous_score, new_score, response_ms) VALUES (1, datetime('now', '-2 days'), 'good', NULL, 2, 1000), (1, datetime('now', '-1 days'), 'easy', 2, 9, 3000), (1, datetime('now'), 'good', 9, 9, 2000)",
+                ],
+            )
+            .await;
+
+            let series = mastery_history_with_pool(&pool, 3)
+                .await
+                .expect("Failed to read mastery history");
+            assert_eq!(series.len(), 1);
+            let counts: Vec<(i64, i64, i64, i64)> = series[0]
+                .points
+                .iter()
+                .map(|point| (point.unseen, point.low, point.mid, point.high))
+                .collect();
+            // alpha climbs from low to high; beta has no log and keeps its
+            // current score; gamma was never studied.
+            assert_eq!(counts, [(1, 1, 1, 0), (1, 0, 1, 1), (1, 0, 1, 1)]);
+
+            let latency = answer_latency_with_pool(&pool, 3)
+                .await
+                .expect("Failed to read latency");
+            assert_eq!(latency.answers, 3);
+            assert_eq!(latency.average_ms, Some(2000.0));
+            let daily: Vec<Option<f64>> = latency
+                .points
+                .iter()
+                .map(|point| point.average_ms)
+                .collect();
+            assert_eq!(daily, [Some(1000.0), Some(3000.0), Some(2000.0)]);
+            assert!(day_count(Some(0)).is_err());
+        });
+    }
+
+    #[test]
+    fn forecast_spreads_due_reviews_and_new_words() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            run_script(
+                &pool,
+                &[
+                    "INSERT INTO word (id, word) VALUES (1, 'alpha'), (2, 'beta'), (3, 'gamma'), (4, 'delta'), (5, 'epsilon'), (6, 'zeta')",
+                    "INSERT INTO word_list (id, name, created_at) VALUES (1, 'CET-4', '2024-01-02'), (2, 'CET-6', '2024-01-01')",
+                    "INSERT INTO word_list_map (word_list_id, word_id) VALUES (1, 1), (1, 2), (1, 3), (1, 4), (1, 5), (2, 3), (2, 6)",
+                    "UPDATE word_list_state SET active_word_list_id = 1 WHERE id = 1",
+                    "INSERT INTO user_word_learning (word_id, proficiency_score, learn_count, due_at) VALUES (1, 3, 1, datetime('now', '-3 days')), (2, 5, 2, datetime('now', '+1 days')), (3, 1, 1, datetime('now', '+30 days'))",
+                    "INSERT INTO study_log (word_id, learned_at, grade) VALUES (3, datetime('now'), 'good')",
+                    "UPDATE study_goal SET new_word_target = 2",
+                ],
+            )
+            .await;
+
+            let forecasts = forecast_reviews_with_pool(&pool, 3)
+                .await
+                .expect("Failed to forecast reviews");
+            assert_eq!(forecasts.len(), 2);
+            let days: Vec<(i64, i64)> = forecasts[0]
+                .days
+                .iter()
+                .map(|day| (day.due, day.new_words))
+                .collect();
+            // Gamma was new today, so only one more new word fits today's goal;
+            // the last unseen word enters tomorrow.
+            assert_eq!(days, [(1, 1), (1, 1), (0, 0)]);
+            assert_eq!(
+                (forecasts[0].total_due, forecasts[0].total_new_words),
+                (2, 2)
+            );
+            // Gamma is shared, but the goal is spent on the active list only.
+            assert_eq!(forecasts[1].word_list_id, 2);
+            assert_eq!(forecasts[1].total_new_words, 0);
+
+            run_script(
+                &pool,
+                &["UPDATE word_list_state SET active_word_list_id = 2 WHERE id = 1"],
+            )
+            .await;
+            let forecasts = forecast_reviews_with_pool(&pool, 3)
+                .await
+                .expect("Failed to forecast reviews");
+            assert_eq!(forecasts[0].total_new_words, 0);
+            let new_words: Vec<i64> = forecasts[1].days.iter().map(|day| day.new_words).collect();
+            assert_eq!(new_words, [1, 0, 0]);
+            assert!(day_count(Some(MAX_DAYS + 1)).is_err());
+        });
+    }
+}
@@ -0,0 +1,1584 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::{Row, SqlitePool};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::backup;
+use crate::scheduler::DEFAULT_EASE_FACTOR;
+use crate::word_bank::{
+    self, Database, DictionaryRecord, ImportSource, ImportSummary, MergePolicy, DEFINITION_COLUMNS,
+};
+
+/// Newer Anki versions ship a zstd-compressed `anki21b` collection next to a
+/// stub `anki2` file, so the entries are tried newest first.
+const COLLECTION_ENTRIES: [&str; 3] = [
+    "collection.anki21b",
+    "collection.anki21",
+    "collection.anki2",
+];
+const FIELD_SEPARATOR: char = '\u{1f}';
+const MEDIA_DIR_NAME: &str = "media";
+/// Minimum interval in days for each proficiency score step, matching the
+/// seeding used when the scheduler was introduced.
+const SCORE_INTERVAL_STEPS: [f64; 10] = [1.0, 3.0, 6.0, 10.0, 15.0, 25.0, 40.0, 60.0, 90.0, 120.0];
+
+#[derive(Debug, Serialize)]
+pub struct AnkiNoteType {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub note_count: i64,
+}
+
+/// Anki field names to read for each word column, optionally limited to one note type.
+#[derive(Debug, Deserialize)]
+pub struct AnkiFieldMapping {
+    pub note_type: Option<String>,
+    pub word: String,
+    pub phonetic: Option<String>,
+    pub part_of_speech_and_meanings: Option<String>,
+    pub example_sentence: Option<String>,
+    pub example_translation: Option<String>,
+    pub audio_uk: Option<String>,
+    pub audio_us: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AnkiImportOptions {
+    /// Carry each note's scheduling state over into `user_word_learning`.
+    pub include_review_history: bool,
+    pub merge_policy: MergePolicy,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyNoteType {
+    name: String,
+    flds: Vec<LegacyField>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyField {
+    name: String,
+    ord: i64,
+}
+
+struct NoteType {
+    name: String,
+    fields: Vec<String>,
+}
+
+struct ReviewHistory {
+    proficiency_score: i64,
+    learn_count: i64,
+    ease_factor: f64,
+    interval_days: f64,
+    repetitions: i64,
+    lapses: i64,
+    last_reviewed_at: Option<i64>,
+    due_at: i64,
+}
+
+/// An Anki package with its collection unpacked to a temporary SQLite file.
+struct AnkiPackage {
+    archive: ZipArchive<fs::File>,
+    collection_path: PathBuf,
+    /// Media file name to the zip entry that stores it.
+    media: HashMap<String, String>,
+}
+
+impl AnkiPackage {
+    fn open(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("无法打开 Anki 文件: {}", path.display()))?;
+        let mut archive = ZipArchive::new(file).context("Anki 文件不是有效的压缩包")?;
+
+        let Some(entry) = COLLECTION_ENTRIES
+            .into_iter()
+            .find(|name| archive.by_name(name).is_ok())
+        else {
+            bail!("Anki 文件中没有找到牌组数据");
+        };
+        let mut bytes = Vec::new();
+        archive
+            .by_name(entry)
+            .context("读取 Anki 牌组数据失败")?
+            .read_to_end(&mut bytes)
+            .context("读取 Anki 牌组数据失败")?;
+        if entry.ends_with("anki21b") {
+            bytes = zstd::decode_all(bytes.as_slice()).context("解压 Anki 牌组数据失败")?;
+        }
+
+        let collection_path = temp_collection_path();
+        fs::write(&collection_path, bytes).context("写入临时牌组文件失败")?;
+
+        let media = read_media_index(&mut archive);
+        Ok(Self {
+            archive,
+            collection_path,
+            media,
+        })
+    }
+
+    async fn connect(&self) -> Result<SqlitePool> {
+        let options = SqliteConnectOptions::new()
+            .filename(&self.collection_path)
+            .read_only(true);
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .context("打开 Anki 牌组数据失败")
+    }
+
+    /// Copies a packaged media file into the local media cache.
+    fn extract_media(&mut self, file_name: &str, media_dir: &Path) -> Result<Option<PathBuf>> {
+        let Some(safe_name) = Path::new(file_name).file_name() else {
+            return Ok(None);
+        };
+        let Some(entry) = self.media.get(file_name) else {
+            return Ok(None);
+        };
+        let target = media_dir.join(safe_name);
+        if !target.exists() {
+            let mut source = self
+                .archive
+                .by_name(entry)
+                .with_context(|| format!("读取 Anki 媒体文件失败: {file_name}"))?;
+            fs::create_dir_all(media_dir)
+                .with_context(|| format!("创建媒体目录失败: {}", media_dir.display()))?;
+            let mut output = fs::File::create(&target)
+                .with_context(|| format!("写入媒体文件失败: {}", target.display()))?;
+            std::io::copy(&mut source, &mut output)
+                .with_context(|| format!("写入媒体文件失败: {}", target.display()))?;
+        }
+        Ok(Some(target))
+    }
+}
+
+impl Drop for AnkiPackage {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.collection_path);
+    }
+}
+
+fn temp_collection_path() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "word-card-anki-{}-{}.sqlite3",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default()
+    ))
+}
+
+/// Legacy packages describe media as a JSON object of `{"0": "file.mp3"}`.
+/// Newer packages use a protobuf index that is not supported, so their media is skipped.
+fn read_media_index(archive: &mut ZipArchive<fs::File>) -> HashMap<String, String> {
+    let mut bytes = Vec::new();
+    let Ok(mut entry) = archive.by_name("media") else {
+        return HashMap::new();
+    };
+    if entry.read_to_end(&mut bytes).is_err() {
+        return HashMap::new();
+    }
+    serde_json::from_slice::<HashMap<String, String>>(&bytes)
+        .map(|index| {
+            index
+                .into_iter()
+                .map(|(entry, file_name)| (file_name, entry))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn media_dir(db: &Database) -> Option<PathBuf> {
+    db.path()
+        .and_then(Path::parent)
+        .map(|dir| dir.join(MEDIA_DIR_NAME))
+}
+
+async fn table_exists(pool: &SqlitePool, table: &str) -> Result<bool> {
+    let exists: Option<String> =
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(table)
+            .fetch_optional(pool)
+            .await
+            .context("读取 Anki 牌组结构失败")?;
+    Ok(exists.is_some())
+}
+
+async fn read_note_types(pool: &SqlitePool) -> Result<HashMap<i64, NoteType>> {
+    let mut note_types = HashMap::new();
+
+    if table_exists(pool, "notetypes").await? {
+        let rows = sqlx::query("SELECT id, name FROM notetypes")
+            .fetch_all(pool)
+            .await
+            .context("读取 Anki 笔记类型失败")?;
+        for row in rows {
+            let id: i64 = row.try_get("id").context("读取 Anki 笔记类型失败")?;
+            let name: String = row.try_get("name").context("读取 Anki 笔记类型失败")?;
+            note_types.insert(
+                id,
+                NoteType {
+                    name,
+                    fields: Vec::new(),
+                },
+            );
+        }
+
+        let rows = sqlx::query("SELECT ntid, name FROM fields ORDER BY ntid, ord")
+            .fetch_all(pool)
+            .await
+            .context("读取 Anki 字段失败")?;
+        for row in rows {
+            let note_type_id: i64 = row.try_get("ntid").context("读取 Anki 字段失败")?;
+            let name: String = row.try_get("name").context("读取 Anki 字段失败")?;
+            if let Some(note_type) = note_types.get_mut(&note_type_id) {
+                note_type.fields.push(name);
+            }
+        }
+        return Ok(note_types);
+    }
+
+    let models: String = sqlx::query_scalar("SELECT models FROM col")
+        .fetch_one(pool)
+        .await
+        .context("读取 Anki 笔记类型失败")?;
+    let models: HashMap<String, LegacyNoteType> =
+        serde_json::from_str(&models).context("解析 Anki 笔记类型失败")?;
+    for (id, mut model) in models {
+        let Ok(id) = id.parse::<i64>() else {
+            continue;
+        };
+        model.flds.sort_by_key(|field| field.ord);
+        note_types.insert(
+            id,
+            NoteType {
+                name: model.name,
+                fields: model.flds.into_iter().map(|field| field.name).collect(),
+            },
+        );
+    }
+    Ok(note_types)
+}
+
+/// Strips the HTML Anki stores in fields down to plain text.
+fn clean_field(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(['<', '[']) {
+        text.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("[sound:") {
+            match tail.find(']') {
+                Some(end) => rest = &tail[end + 1..],
+                None => {
+                    rest = "";
+                }
+            }
+        } else if tail.starts_with('<') {
+            let Some(end) = tail.find('>') else {
+                text.push_str(tail);
+                rest = "";
+                break;
+            };
+            let tag = tail[1..end].trim_start_matches('/').to_ascii_lowercase();
+            if tag.starts_with("br") || tag.starts_with("div") || tag.starts_with("p") {
+                text.push('\n');
+            }
+            rest = &tail[end + 1..];
+        } else {
+            text.push('[');
+            rest = &tail[1..];
+        }
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn sound_file_name(value: &str) -> Option<&str> {
+    let start = value.find("[sound:")? + "[sound:".len();
+    let end = value[start..].find(']')? + start;
+    let name = value[start..end].trim();
+    (!name.is_empty()).then_some(name)
+}
+
+fn proficiency_for_interval(interval_days: f64) -> i64 {
+    SCORE_INTERVAL_STEPS
+        .iter()
+        .filter(|step| interval_days >= **step)
+        .count() as i64
+}
+
+/// Reads the scheduling state of each note's first card.
+async fn read_review_history(pool: &SqlitePool) -> Result<HashMap<i64, ReviewHistory>> {
+    let created_at: i64 = sqlx::query_scalar("SELECT crt FROM col")
+        .fetch_one(pool)
+        .await
+        .context("读取 Anki 牌组创建时间失败")?;
+    let rows = sqlx::query(
+        r#"
+SELECT
+  c.nid,
+  c.type,
+  c.queue,
+  c.due,
+  c.ivl,
+  c.factor,
+  c.reps,
+  c.lapses,
+  (SELECT MAX(r.id) FROM revlog r WHERE r.cid = c.id) AS last_review_ms
+FROM cards c
+WHERE c.type != 0 AND c.reps > 0
+ORDER BY c.nid, c.ord
+"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("读取 Anki 复习记录失败")?;
+
+    let mut history = HashMap::new();
+    for row in rows {
+        let note_id: i64 = row.try_get("nid").context("读取 Anki 卡片失败")?;
+        if history.contains_key(&note_id) {
+            continue;
+        }
+        let card_type: i64 = row.try_get("type").context("读取 Anki 卡片失败")?;
+        let queue: i64 = row.try_get("queue").context("读取 Anki 卡片失败")?;
+        let due: i64 = row.try_get("due").context("读取 Anki 卡片失败")?;
+        let interval: i64 = row.try_get("ivl").context("读取 Anki 卡片失败")?;
+        let factor: i64 = row.try_get("factor").context("读取 Anki 卡片失败")?;
+        let reps: i64 = row.try_get("reps").context("读取 Anki 卡片失败")?;
+        let lapses: i64 = row.try_get("lapses").context("读取 Anki 卡片失败")?;
+        let last_review_ms: Option<i64> = row
+            .try_get("last_review_ms")
+            .context("读取 Anki 复习记录失败")?;
+
+        // Positive intervals are days, negative ones are learning steps in seconds.
+        let interval_days = if interval >= 0 {
+            interval as f64
+        } else {
+            -interval as f64 / 86_400.0
+        };
+        // Intraday learning cards store a timestamp, review cards a day offset.
+        let due_at = if queue == 1 || (card_type != 2 && queue != 3) {
+            due
+        } else {
+            created_at + due * 86_400
+        };
+        let ease_factor = if factor > 0 {
+            factor as f64 / 1000.0
+        } else {
+            DEFAULT_EASE_FACTOR
+        };
+
+        history.insert(
+            note_id,
+            ReviewHistory {
+                proficiency_score: proficiency_for_interval(interval_days),
+                learn_count: (reps - lapses).max(0),
+                ease_factor,
+                interval_days,
+                repetitions: (reps - lapses).max(0),
+                lapses,
+                last_reviewed_at: last_review_ms.map(|ms| ms / 1000),
+                due_at,
+            },
+        );
+    }
+    Ok(history)
+}
+
+/// Keeps whichever progress was reviewed more recently.
+async fn write_review_history(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    word_id: i64,
+    history: &ReviewHistory,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+INSERT INTO user_word_learning (
+  word_id,
+  proficiency_score,
+  last_learned_at,
+  learn_count,
+  ease_factor,
+  interval_days,
+  repetitions,
+  lapses,
+  due_at
+)
+VALUES (?, ?, datetime(?, 'unixepoch'), ?, ?, ?, ?, ?, datetime(?, 'unixepoch'))
+ON CONFLICT(word_id) DO UPDATE SET
+  proficiency_score = excluded.proficiency_score,
+  last_learned_at = excluded.last_learned_at,
+  learn_count = excluded.learn_count,
+  ease_factor = excluded.ease_factor,
+  interval_days = excluded.interval_days,
+  repetitions = excluded.repetitions,
+  lapses = excluded.lapses,
+  due_at = excluded.due_at
+WHERE COALESCE(excluded.last_learned_at, '') > COALESCE(user_word_learning.last_learned_at, '')
+"#,
+    )
+    .bind(word_id)
+    .bind(history.proficiency_score)
+    .bind(history.last_reviewed_at)
+    .bind(history.learn_count)
+    .bind(history.ease_factor)
+    .bind(history.interval_days)
+    .bind(history.repetitions)
+    .bind(history.lapses)
+    .bind(history.due_at)
+    .execute(&mut **tx)
+    .await
+    .context("写入 Anki 复习记录失败")?;
+    Ok(())
+}
+
+async fn inspect_package(path: &Path) -> Result<Vec<AnkiNoteType>> {
+    let package = AnkiPackage::open(path)?;
+    let pool = package.connect().await?;
+    let result = async {
+        let note_types = read_note_types(&pool).await?;
+        let counts = sqlx::query("SELECT mid, COUNT(1) AS note_count FROM notes GROUP BY mid")
+            .fetch_all(&pool)
+            .await
+            .context("统计 Anki 笔记失败")?;
+        let mut summary: Vec<AnkiNoteType> = counts
+            .into_iter()
+            .filter_map(|row| {
+                let mid: i64 = row.try_get("mid").ok()?;
+                let note_count: i64 = row.try_get("note_count").ok()?;
+                let note_type = note_types.get(&mid)?;
+                Some(AnkiNoteType {
+                    name: note_type.name.clone(),
+                    fields: note_type.fields.clone(),
+                    note_count,
+                })
+            })
+            .collect();
+        summary.sort_by_key(|note_type| std::cmp::Reverse(note_type.note_count));
+        Ok(summary)
+    }
+    .await;
+    pool.close().await;
+    result
+}
+
+pub async fn inspect_anki_package(path: &str) -> Result<Vec<AnkiNoteType>> {
+    inspect_package(Path::new(path.trim())).await
+}
+
+fn field_value<'a>(
+    fields: &'a [String],
+    values: &'a [&'a str],
+    name: Option<&str>,
+) -> Option<&'a str> {
+    let name = name?;
+    let index = fields.iter().position(|field| field == name)?;
+    values.get(index).copied()
+}
+
+async fn import_package_with_pool(
+    pool: &SqlitePool,
+    name: &str,
+    path: &Path,
+    mapping: &AnkiFieldMapping,
+    options: &AnkiImportOptions,
+    overwrite: bool,
+    media_dir: Option<&Path>,
+) -> Result<ImportSummary> {
+    if mapping.word.trim().is_empty() {
+        bail!("请选择作为单词的 Anki 字段");
+    }
+
+    let mut package = AnkiPackage::open(path)?;
+    let collection = package.connect().await?;
+    let loaded = async {
+        let note_types = read_note_types(&collection).await?;
+        let notes = sqlx::query("SELECT id, mid, flds FROM notes ORDER BY id")
+            .fetch_all(&collection)
+            .await
+            .context("读取 Anki 笔记失败")?;
+        let history = if options.include_review_history {
+            read_review_history(&collection).await?
+        } else {
+            HashMap::new()
+        };
+        Ok::<_, anyhow::Error>((note_types, notes, history))
+    }
+    .await;
+    collection.close().await;
+    let (note_types, notes, history) = loaded?;
+
+    let source_name = path.file_name().and_then(|name| name.to_str());
+    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+    let target = word_bank::prepare_import_list(
+        &mut tx,
+        name,
+        overwrite,
+        &ImportSource {
+            kind: "anki",
+            name: source_name,
+            merge_policy: options.merge_policy,
+        },
+    )
+    .await?;
+
+    let mut total = 0_u64;
+    let mut upserted = 0_u64;
+    let mut skipped = 0_u64;
+
+    for note in notes {
+        let note_id: i64 = note.try_get("id").context("读取 Anki 笔记失败")?;
+        let mid: i64 = note.try_get("mid").context("读取 Anki 笔记失败")?;
+        let flds: String = note.try_get("flds").context("读取 Anki 笔记失败")?;
+        let Some(note_type) = note_types.get(&mid) else {
+            continue;
+        };
+        if mapping
+            .note_type
+            .as_deref()
+            .is_some_and(|wanted| wanted != note_type.name)
+        {
+            continue;
+        }
+
+        total += 1;
+        let values: Vec<&str> = flds.split(FIELD_SEPARATOR).collect();
+        let text = |field: &Option<String>| {
+            field_value(&note_type.fields, &values, field.as_deref()).map(clean_field)
+        };
+        let mut audio = |field: &Option<String>| -> Result<Option<String>> {
+            let Some(value) = field_value(&note_type.fields, &values, field.as_deref()) else {
+                return Ok(None);
+            };
+            if let Some(file_name) = sound_file_name(value) {
+                let Some(media_dir) = media_dir else {
+                    return Ok(None);
+                };
+                return Ok(package
+                    .extract_media(file_name, media_dir)?
+                    .map(|path| path.display().to_string()));
+            }
+            let cleaned = clean_field(value);
+            Ok(
+                (cleaned.starts_with("http://") || cleaned.starts_with("https://"))
+                    .then_some(cleaned),
+            )
+        };
+
+        let record = DictionaryRecord {
+            word: field_value(&note_type.fields, &values, Some(&mapping.word))
+                .map(clean_field)
+                .unwrap_or_default(),
+            phonetic: text(&mapping.phonetic),
+            part_of_speech_and_meanings: text(&mapping.part_of_speech_and_meanings),
+            example_sentence: text(&mapping.example_sentence),
+            example_translation: text(&mapping.example_translation),
+            audio_uk: audio(&mapping.audio_uk)?,
+            audio_us: audio(&mapping.audio_us)?,
+        };
+        let Some(upserted_word) =
+            word_bank::upsert_dictionary_record(&mut tx, &target, record, options.merge_policy)
+                .await?
+        else {
+            skipped += 1;
+            continue;
+        };
+        if let Some(review) = history.get(&note_id) {
+            write_review_history(&mut tx, upserted_word.id, review).await?;
+        }
+        upserted += 1;
+    }
+
+    tx.commit().await.context("提交数据库事务失败")?;
+    Ok(ImportSummary {
+        total,
+        upserted,
+        skipped,
+        merge_policy: options.merge_policy,
+        ..ImportSummary::default()
+    })
+}
+
+pub async fn import_anki_package(
+    db: &Database,
+    name: &str,
+    path: &str,
+    mapping: &AnkiFieldMapping,
+    options: &AnkiImportOptions,
+    overwrite: bool,
+) -> Result<ImportSummary> {
+    let pool = db.pool()?;
+    if overwrite {
+        backup::create_automatic_backup(db, "overwrite-import").await?;
+    }
+    let media_dir = media_dir(db);
+    import_package_with_pool(
+        &pool,
+        name,
+        Path::new(path.trim()),
+        mapping,
+        options,
+        overwrite,
+        media_dir.as_deref(),
+    )
+    .await
+}
+
+/// Legacy (schema 11) collection layout, which every Anki client can import.
+const EXPORT_SCHEMA: [&str; 12] = [
+    "CREATE TABLE col (id INTEGER PRIMARY KEY, crt INTEGER NOT NULL, mod INTEGER NOT NULL, scm INTEGER NOT NULL, ver INTEGER NOT NULL, dty INTEGER NOT NULL, usn INTEGER NOT NULL, ls INTEGER NOT NULL, conf TEXT NOT NULL, models TEXT NOT NULL, decks TEXT NOT NULL, dconf TEXT NOT NULL, tags TEXT NOT NULL)",
+    "CREATE TABLE notes (id INTEGER PRIMARY KEY, guid TEXT NOT NULL, mid INTEGER NOT NULL, mod INTEGER NOT NULL, usn INTEGER NOT NULL, tags TEXT NOT NULL, flds TEXT NOT NULL, sfld INTEGER NOT NULL, csum INTEGER NOT NULL, flags INTEGER NOT NULL, data TEXT NOT NULL)",
+    "CREATE TABLE cards (id INTEGER PRIMARY KEY, nid INTEGER NOT NULL, did INTEGER NOT NULL, ord INTEGER NOT NULL, mod INTEGER NOT NULL, usn INTEGER NOT NULL, type INTEGER NOT NULL, queue INTEGER NOT NULL, due INTEGER NOT NULL, ivl INTEGER NOT NULL, factor INTEGER NOT NULL, reps INTEGER NOT NULL, lapses INTEGER NOT NULL, left INTEGER NOT NULL, odue INTEGER NOT NULL, odid INTEGER NOT NULL, flags INTEGER NOT NULL, data TEXT NOT NULL)",
+    "CREATE TABLE revlog (id INTEGER PRIMARY KEY, cid INTEGER NOT NULL, usn INTEGER NOT NULL, ease INTEGER NOT NULL, ivl INTEGER NOT NULL, lastIvl INTEGER NOT NULL, factor INTEGER NOT NULL, time INTEGER NOT NULL, type INTEGER NOT NULL)",
+    "CREATE TABLE graves (usn INTEGER NOT NULL, oid INTEGER NOT NULL, type INTEGER NOT NULL)",
+    "CREATE INDEX ix_notes_usn ON notes (usn)",
+    "CREATE INDEX ix_cards_usn ON cards (usn)",
+    "CREATE INDEX ix_revlog_usn ON revlog (usn)",
+    "CREATE INDEX ix_cards_nid ON cards (nid)",
+    "CREATE INDEX ix_cards_sched ON cards (did, queue, due)",
+    "CREATE INDEX ix_revlog_cid ON revlog (cid)",
+    "CREATE INDEX ix_notes_csum ON notes (csum)",
+];
+/// Fixed so repeated exports share one note type after import.
+const EXPORT_MODEL_ID: i64 = 1_700_000_000_000;
+const EXPORT_DECK_ID_BASE: i64 = 1_700_000_000_000;
+const EXPORT_FIELDS: [&str; 7] = [
+    "Word",
+    "Phonetic",
+    "Meanings",
+    "Example",
+    "Example Translation",
+    "Audio UK",
+    "Audio US",
+];
+const GUID_ALPHABET: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!#$%&()*+,-./:;<=>?@[]^_`{|}~";
+
+#[derive(Debug, Serialize)]
+pub struct AnkiExportSummary {
+    pub path: String,
+    pub note_count: u64,
+    pub media_count: u64,
+}
+
+struct ExportCard {
+    note_id: i64,
+    fields: Vec<String>,
+    review: Option<ExportReview>,
+}
+
+struct ExportReview {
+    due_day: i64,
+    interval_days: i64,
+    factor: i64,
+    reps: i64,
+    lapses: i64,
+    last_reviewed_at: Option<i64>,
+}
+
+fn escape_field(value: Option<&str>) -> String {
+    value
+        .unwrap_or_default()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+fn sha1_prefix(value: &str) -> [u8; 8] {
+    let digest = Sha1::digest(value.as_bytes());
+    let mut prefix = [0_u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    prefix
+}
+
+/// Stable per-word guid so re-exported decks update the same notes in Anki.
+fn note_guid(word: &str) -> String {
+    let mut value = u64::from_be_bytes(sha1_prefix(&format!("word-card:{word}")));
+    let base = GUID_ALPHABET.len() as u64;
+    let mut guid = String::new();
+    while value > 0 {
+        guid.push(GUID_ALPHABET[(value % base) as usize] as char);
+        value /= base;
+    }
+    guid
+}
+
+/// Anki's duplicate check reads the first 32 bits of the sort field's SHA-1.
+fn field_checksum(sort_field: &str) -> i64 {
+    let prefix = sha1_prefix(sort_field);
+    i64::from(u32::from_be_bytes([
+        prefix[0], prefix[1], prefix[2], prefix[3],
+    ]))
+}
+
+/// Audio is only bundled when it points at a file already cached on disk.
+fn local_media_path(value: Option<&str>) -> Option<PathBuf> {
+    let value = value?.trim();
+    if value.contains("://") {
+        return None;
+    }
+    let path = PathBuf::from(value);
+    (path.is_absolute() && path.is_file()).then_some(path)
+}
+
+fn export_collection_json(
+    deck_id: i64,
+    deck_name: &str,
+    note_count: usize,
+    now: i64,
+) -> (String, String, String, String) {
+    let fields: Vec<serde_json::Value> = EXPORT_FIELDS
+        .iter()
+        .enumerate()
+        .map(|(ord, name)| {
+            json!({
+                "name": name,
+                "ord": ord,
+                "sticky": false,
+                "rtl": false,
+                "font": "Arial",
+                "size": 20,
+                "media": [],
+            })
+        })
+        .collect();
+    let models = json!({
+        EXPORT_MODEL_ID.to_string(): {
+            "id": EXPORT_MODEL_ID,
+            "name": "word-card",
+            "type": 0,
+            "mod": now,
+            "usn": -1,
+            "sortf": 0,
+            "did": deck_id,
+            "tags": [],
+            "vers": [],
+            "flds": fields,
+            "tmpls": [{
+                "name": "Card 1",
+                "ord": 0,
+                "qfmt": "{{Word}}<br>{{Phonetic}}{{Audio UK}}",
+                "afmt": "{{FrontSide}}<hr id=answer>{{Meanings}}<br><br>{{Example}}<br>{{Example Translation}}{{Audio US}}",
+                "bqfmt": "",
+                "bafmt": "",
+                "did": null,
+            }],
+            "req": [[0, "any", [0]]],
+            "css": ".card { font-family: arial; font-size: 20px; text-align: center; }",
+            "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+            "latexPost": "\\end{document}",
+        }
+    });
+    let deck = |id: i64, name: &str| {
+        json!({
+            "id": id,
+            "name": name,
+            "mod": now,
+            "usn": -1,
+            "desc": "",
+            "dyn": 0,
+            "conf": 1,
+            "collapsed": false,
+            "browserCollapsed": false,
+            "extendNew": 0,
+            "extendRev": 0,
+            "newToday": [0, 0],
+            "revToday": [0, 0],
+            "lrnToday": [0, 0],
+            "timeToday": [0, 0],
+        })
+    };
+    let decks = json!({
+        "1": deck(1, "Default"),
+        deck_id.to_string(): deck(deck_id, deck_name),
+    });
+    let deck_config = json!({
+        "1": {
+            "id": 1,
+            "name": "Default",
+            "mod": 0,
+            "usn": 0,
+            "dyn": false,
+            "maxTaken": 60,
+            "timer": 0,
+            "autoplay": true,
+            "replayq": true,
+            "new": {
+                "delays": [1, 10],
+                "ints": [1, 4, 0],
+                "initialFactor": 2500,
+                "order": 1,
+                "perDay": 20,
+                "bury": false,
+            },
+            "rev": {
+                "perDay": 200,
+                "ease4": 1.3,
+                "ivlFct": 1,
+                "maxIvl": 36500,
+                "hardFactor": 1.2,
+                "bury": false,
+            },
+            "lapse": {
+                "delays": [10],
+                "mult": 0,
+                "minInt": 1,
+                "leechFails": 8,
+                "leechAction": 1,
+            },
+        }
+    });
+    let conf = json!({
+        "nextPos": note_count + 1,
+        "curDeck": deck_id,
+        "activeDecks": [deck_id],
+        "curModel": EXPORT_MODEL_ID,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+        "collapseTime": 1200,
+        "estTimes": true,
+        "dueCounts": true,
+        "newSpread": 0,
+        "timeLim": 0,
+        "schedVer": 2,
+    });
+    (
+        conf.to_string(),
+        models.to_string(),
+        decks.to_string(),
+        deck_config.to_string(),
+    )
+}
+
+async fn write_export_collection(
+    collection_path: &Path,
+    deck_id: i64,
+    deck_name: &str,
+    cards: &[ExportCard],
+    collection_created_at: i64,
+    now_ms: i64,
+) -> Result<()> {
+    let options = SqliteConnectOptions::new()
+        .filename(collection_path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Delete);
+    let collection = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .context("创建 Anki 牌组数据失败")?;
+
+    let result = async {
+        let mut tx = collection.begin().await.context("开启数据库事务失败")?;
+        for statement in EXPORT_SCHEMA {
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .context("创建 Anki 牌组结构失败")?;
+        }
+
+        let now = now_ms / 1000;
+        let (conf, models, decks, deck_config) =
+            export_collection_json(deck_id, deck_name, cards.len(), now);
+        sqlx::query(
+            r#"
+INSERT INTO col (id, crt, mod, scm, ver, dty, usn, ls, conf, models, decks, dconf, tags)
+VALUES (1, ?, ?, ?, 11, 0, 0, 0, ?, ?, ?, ?, '{}')
+"#,
+        )
+        .bind(collection_created_at)
+        .bind(now_ms)
+        .bind(now_ms)
+        .bind(conf)
+        .bind(models)
+        .bind(decks)
+        .bind(deck_config)
+        .execute(&mut *tx)
+        .await
+        .context("写入 Anki 牌组信息失败")?;
+
+        for (position, card) in cards.iter().enumerate() {
+            let word = card.fields.first().map(String::as_str).unwrap_or_default();
+            sqlx::query(
+                r#"
+INSERT INTO notes (id, guid, mid, mod, usn, tags, flds, sfld, csum, flags, data)
+VALUES (?, ?, ?, ?, -1, '', ?, ?, ?, 0, '')
+"#,
+            )
+            .bind(card.note_id)
+            .bind(note_guid(word))
+            .bind(EXPORT_MODEL_ID)
+            .bind(now)
+            .bind(card.fields.join(&FIELD_SEPARATOR.to_string()))
+            .bind(word)
+            .bind(field_checksum(word))
+            .execute(&mut *tx)
+            .await
+            .context("写入 Anki 笔记失败")?;
+
+            // New cards are queued by position; reviewed cards keep their schedule.
+            let (card_type, due, interval, factor, reps, lapses) = match &card.review {
+                Some(review) => (
+                    2,
+                    review.due_day,
+                    review.interval_days,
+                    review.factor,
+                    review.reps,
+                    review.lapses,
+                ),
+                None => (0, position as i64 + 1, 0, 0, 0, 0),
+            };
+            sqlx::query(
+                r#"
+INSERT INTO cards (
+  id, nid, did, ord, mod, usn, type, queue, due, ivl, factor, reps, lapses, left, odue, odid, flags, data
+)
+VALUES (?, ?, ?, 0, ?, -1, ?, ?, ?, ?, ?, ?, ?, 0, 0, 0, 0, '')
+"#,
+            )
+            .bind(card.note_id)
+            .bind(card.note_id)
+            .bind(deck_id)
+            .bind(now)
+            .bind(card_type)
+            .bind(card_type)
+            .bind(due)
+            .bind(interval)
+            .bind(factor)
+            .bind(reps)
+            .bind(lapses)
+            .execute(&mut *tx)
+            .await
+            .context("写入 Anki 卡片失败")?;
+
+            if let Some(reviewed_at) = card.review.as_ref().and_then(|review| review.last_reviewed_at) {
+                sqlx::query(
+                    r#"
+INSERT INTO revlog (id, cid, usn, ease, ivl, lastIvl, factor, time, type)
+VALUES (?, ?, -1, 3, ?, 0, ?, 0, 1)
+"#,
+                )
+                .bind(reviewed_at * 1000 + position as i64)
+                .bind(card.note_id)
+                .bind(interval)
+                .bind(factor)
+                .execute(&mut *tx)
+                .await
+                .context("写入 Anki 复习记录失败")?;
+            }
+        }
+
+        tx.commit().await.context("提交数据库事务失败")?;
+        Ok(())
+    }
+    .await;
+    collection.close().await;
+    result
+}
+
+async fn export_package_with_pool(
+    pool: &SqlitePool,
+    word_list_id: i64,
+    path: &Path,
+) -> Result<AnkiExportSummary> {
+    let deck_name: Option<String> = sqlx::query_scalar("SELECT name FROM word_list WHERE id = ?")
+        .bind(word_list_id)
+        .fetch_optional(pool)
+        .await
+        .context("检查词库是否存在失败")?;
+    let Some(deck_name) = deck_name else {
+        bail!("词库不存在");
+    };
+
+    let (collection_created_at, now_ms): (i64, i64) = sqlx::query_as(
+        "SELECT CAST(strftime('%s', 'now', 'start of day') AS INTEGER), CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)",
+    )
+    .fetch_one(pool)
+    .await
+    .context("读取当前时间失败")?;
+
+    let query = format!(
+        r#"
+SELECT
+  w.word,
+  {DEFINITION_COLUMNS},
+  uwl.word_id AS learning_word_id,
+  COALESCE(uwl.learn_count, 0) AS learn_count,
+  COALESCE(uwl.lapses, 0) AS lapses,
+  COALESCE(uwl.ease_factor, 0) AS ease_factor,
+  COALESCE(uwl.interval_days, 0) AS interval_days,
+  CAST(strftime('%s', uwl.last_learned_at) AS INTEGER) AS last_learned_unix,
+  CAST(julianday(COALESCE(uwl.due_at, 'now')) - julianday('now', 'start of day') AS INTEGER) AS due_day
+FROM word_list_map wlm
+JOIN word w ON w.id = wlm.word_id
+LEFT JOIN word_definition wd ON wd.word_id = w.id AND wd.word_list_id = wlm.word_list_id
+LEFT JOIN user_word_learning uwl ON uwl.word_id = w.id
+WHERE wlm.word_list_id = ?
+ORDER BY w.id
+"#
+    );
+    let rows = sqlx::query(&query)
+        .bind(word_list_id)
+        .fetch_all(pool)
+        .await
+        .context("读取词库单词失败")?;
+
+    let mut media: Vec<(String, PathBuf)> = Vec::new();
+    let mut cards = Vec::with_capacity(rows.len());
+    for (position, row) in rows.into_iter().enumerate() {
+        let text = |column: &str| -> Result<Option<String>> {
+            row.try_get(column)
+                .with_context(|| format!("读取单词字段失败: {column}"))
+        };
+        // Local files are bundled as media; anything else, such as a remote
+        // URL, stays in the field as text so it is not lost.
+        let mut sound = |column: &str| -> Result<String> {
+            let value = text(column)?;
+            let Some(source) = local_media_path(value.as_deref()) else {
+                return Ok(escape_field(value.as_deref().map(str::trim)));
+            };
+            let Some(file_name) = source.file_name().and_then(|name| name.to_str()) else {
+                return Ok(escape_field(value.as_deref().map(str::trim)));
+            };
+            if let Some((name, _)) = media.iter().find(|(_, path)| *path == source) {
+                return Ok(format!("[sound:{name}]"));
+            }
+            // Another file already took this name, so prefix a hash of the path.
+            let hash: String = sha1_prefix(&source.to_string_lossy())[..4]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            let mut name = file_name.to_string();
+            let mut attempt = 0;
+            while media.iter().any(|(taken, _)| *taken == name) {
+                attempt += 1;
+                name = match attempt {
+                    1 => format!("{hash}-{file_name}"),
+                    _ => format!("{hash}-{attempt}-{file_name}"),
+                };
+            }
+            media.push((name.clone(), source));
+            Ok(format!("[sound:{name}]"))
+        };
+
+        let fields = vec![
+            escape_field(Some(&text("word")?.unwrap_or_default())),
+            escape_field(text("phonetic")?.as_deref()),
+            escape_field(text("part_of_speech_and_meanings")?.as_deref()),
+            escape_field(text("example_sentence")?.as_deref()),
+            escape_field(text("example_translation")?.as_deref()),
+            sound("audio_uk")?,
+            sound("audio_us")?,
+        ];
+
+        let learning_word_id: Option<i64> = row
+            .try_get("learning_word_id")
+            .context("读取学习进度失败")?;
+        let learn_count: i64 = row.try_get("learn_count").context("读取学习进度失败")?;
+        let lapses: i64 = row.try_get("lapses").context("读取学习进度失败")?;
+        let review = if learning_word_id.is_some() && learn_count + lapses > 0 {
+            let ease_factor: f64 = row.try_get("ease_factor").context("读取学习进度失败")?;
+            let interval_days: f64 = row.try_get("interval_days").context("读取学习进度失败")?;
+            Some(ExportReview {
+                due_day: row.try_get("due_day").context("读取学习进度失败")?,
+                // Anki review cards need a whole-day interval; relearning steps round up to one.
+                interval_days: (interval_days.round() as i64).max(1),
+                factor: (ease_factor * 1000.0).round() as i64,
+                reps: learn_count + lapses,
+                lapses,
+                last_reviewed_at: row
+                    .try_get("last_learned_unix")
+                    .context("读取学习进度失败")?,
+            })
+        } else {
+            None
+        };
+
+        cards.push(ExportCard {
+            note_id: now_ms + position as i64,
+            fields,
+            review,
+        });
+    }
+
+    let collection_path = temp_collection_path();
+    let deck_id = EXPORT_DECK_ID_BASE + word_list_id;
+    let written = write_export_collection(
+        &collection_path,
+        deck_id,
+        &deck_name,
+        &cards,
+        collection_created_at,
+        now_ms,
+    )
+    .await
+    .and_then(|()| write_package(path, &collection_path, &media));
+    let _ = fs::remove_file(&collection_path);
+    written?;
+
+    Ok(AnkiExportSummary {
+        path: path.display().to_string(),
+        note_count: cards.len() as u64,
+        media_count: media.len() as u64,
+    })
+}
+
+fn write_package(path: &Path, collection_path: &Path, media: &[(String, PathBuf)]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建导出目录失败: {}", parent.display()))?;
+    }
+    let file = fs::File::create(path)
+        .with_context(|| format!("创建 Anki 文件失败: {}", path.display()))?;
+    let mut writer = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    writer
+        .start_file("collection.anki2", options)
+        .context("写入 Anki 牌组数据失败")?;
+    let mut collection = fs::File::open(collection_path).context("读取临时牌组文件失败")?;
+    std::io::copy(&mut collection, &mut writer).context("写入 Anki 牌组数据失败")?;
+
+    let mut index = serde_json::Map::new();
+    for (entry, (file_name, source)) in media.iter().enumerate() {
+        writer
+            .start_file(entry.to_string(), options)
+            .context("写入 Anki 媒体文件失败")?;
+        let mut source = fs::File::open(source)
+            .with_context(|| format!("读取媒体文件失败: {}", source.display()))?;
+        std::io::copy(&mut source, &mut writer).context("写入 Anki 媒体文件失败")?;
+        index.insert(entry.to_string(), json!(file_name));
+    }
+    writer
+        .start_file("media", options)
+        .context("写入 Anki 媒体索引失败")?;
+    writer
+        .write_all(serde_json::Value::Object(index).to_string().as_bytes())
+        .context("写入 Anki 媒体索引失败")?;
+    writer.finish().context("写入 Anki 文件失败")?;
+    Ok(())
+}
+
+pub async fn export_anki_package(
+    db: &Database,
+    word_list_id: i64,
+    path: &str,
+) -> Result<AnkiExportSummary> {
+    let target = PathBuf::from(path.trim());
+    if target.as_os_str().is_empty() {
+        bail!("导出路径不能为空");
+    }
+    let pool = db.pool()?;
+    export_package_with_pool(&pool, word_list_id, &target).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::async_runtime;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "word-card-{name}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos())
+                .unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).expect("Failed to create temp dir");
+        dir
+    }
+
+    async fn setup_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(SqliteConnectOptions::new().filename(":memory:"))
+            .await
+            .expect("Failed to open test database");
+        word_bank::ensure_schema(&pool)
+            .await
+            .expect("Failed to ensure schema");
+        pool
+    }
+
+    /// Builds a legacy `.apkg` with one note type, two notes and one reviewed card.
+    async fn write_test_package(dir: &Path) -> PathBuf {
+        let collection_path = dir.join("collection.anki2");
+        let collection = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(&collection_path)
+                    .create_if_missing(true),
+            )
+            .await
+            .expect("Failed to create collection");
+        let models = r#"{"1001": {"name": "Vocab", "flds": [
+            {"name": "Back", "ord": 1}, {"name": "Front", "ord": 0}, {"name": "Sound", "ord": 2}
+        ]}}"#;
+        for statement in [
+            "CREATE TABLE col (id INTEGER PRIMARY KEY, crt INTEGER NOT NULL, models TEXT NOT NULL)",
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, mid INTEGER NOT NULL, flds TEXT NOT NULL)",
+            "CREATE TABLE cards (id INTEGER PRIMARY KEY, nid INTEGER, ord INTEGER, type INTEGER, queue INTEGER, due INTEGER, ivl INTEGER, factor INTEGER, reps INTEGER, lapses INTEGER)",
+            "CREATE TABLE revlog (id INTEGER PRIMARY KEY, cid INTEGER)",
+        ] {
+            sqlx::query(statement)
+                .execute(&collection)
+                .await
+                .expect("Failed to create collection table");
+        }
+        sqlx::query("INSERT INTO col (id, crt, models) VALUES (1, 1700000000, ?)")
+            .bind(models)
+            .execute(&collection)
+            .await
+            .expect("Failed to insert col");
+        for (id, flds) in [
+            (
+                1,
+                "apple\u{1f}n. 苹果<br>&nbsp;n. 苹果树\u{1f}[sound:apple.mp3]",
+            ),
+            (2, "<b></b>\u{1f}empty\u{1f}"),
+        ] {
+            sqlx::query("INSERT INTO notes (id, mid, flds) VALUES (?, 1001, ?)")
+                .bind(id)
+                .bind(flds)
+                .execute(&collection)
+                .await
+                .expect("Failed to insert note");
+        }
+        sqlx::query("INSERT INTO cards VALUES (10, 1, 0, 2, 2, 30, 12, 2300, 5, 1)")
+            .execute(&collection)
+            .await
+            .expect("Failed to insert card");
+        sqlx::query("INSERT INTO revlog (id, cid) VALUES (1702592000000, 10)")
+            .execute(&collection)
+            .await
+            .expect("Failed to insert revlog");
+        collection.close().await;
+
+        let package_path = dir.join("deck.apkg");
+        let mut writer =
+            ZipWriter::new(fs::File::create(&package_path).expect("Failed to create apkg"));
+        let options = SimpleFileOptions::default();
+        writer
+            .start_file("collection.anki2", options)
+            .expect("Failed to add collection");
+        writer
+            .write_all(&fs::read(&collection_path).expect("Failed to read collection"))
+            .expect("Failed to write collection");
+        writer
+            .start_file("media", options)
+            .expect("Failed to add media");
+        writer
+            .write_all(br#"{"0": "apple.mp3"}"#)
+            .expect("Failed to write media index");
+        writer
+            .start_file("0", options)
+            .expect("Failed to add audio");
+        writer.write_all(b"ID3").expect("Failed to write audio");
+        writer.finish().expect("Failed to finish apkg");
+        package_path
+    }
+
+    #[test]
+    fn cleans_anki_field_markup() {
+        assert_eq!(
+            clean_field("<div>n. 苹果</div><div>v. &lt;吃&gt;&nbsp;</div>[sound:a.mp3]"),
+            "n. 苹果\nv. <吃>"
+        );
+        assert_eq!(sound_file_name("x [sound:uk.mp3]"), Some("uk.mp3"));
+        assert_eq!(proficiency_for_interval(0.0), 0);
+        assert_eq!(proficiency_for_interval(12.0), 4);
+    }
+
+    #[test]
+    fn imports_notes_media_and_review_history() {
+        async_runtime::block_on(async {
+            let dir = temp_dir("anki-import");
+            let package_path = write_test_package(&dir).await;
+
+            let note_types = inspect_package(&package_path)
+                .await
+                .expect("Failed to inspect package");
+            assert_eq!(note_types.len(), 1);
+            assert_eq!(note_types[0].fields, ["Front", "Back", "Sound"]);
+            assert_eq!(note_types[0].note_count, 2);
+
+            let pool = setup_pool().await;
+            let mapping = AnkiFieldMapping {
+                note_type: Some("Vocab".to_string()),
+                word: "Front".to_string(),
+                phonetic: None,
+                part_of_speech_and_meanings: Some("Back".to_string()),
+                example_sentence: None,
+                example_translation: None,
+                audio_uk: Some("Sound".to_string()),
+                audio_us: Some("Missing".to_string()),
+            };
+            let media_dir = dir.join(MEDIA_DIR_NAME);
+            let summary = import_package_with_pool(
+                &pool,
+                "anki",
+                &package_path,
+                &mapping,
+                &AnkiImportOptions {
+                    include_review_history: true,
+                    ..AnkiImportOptions::default()
+                },
+                false,
+                Some(&media_dir),
+            )
+            .await
+            .expect("Failed to import package");
+            assert_eq!(summary.total, 2);
+            assert_eq!(summary.upserted, 1);
+            assert_eq!(summary.skipped, 1);
+
+            let row = sqlx::query(
+                "SELECT part_of_speech_and_meanings, audio_uk, audio_us FROM word WHERE word = 'apple'",
+            )
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to load word");
+            let meanings: String = row.get("part_of_speech_and_meanings");
+            let audio_uk: String = row.get("audio_uk");
+            let audio_us: Option<String> = row.get("audio_us");
+            assert_eq!(meanings, "n. 苹果\nn. 苹果树");
+            assert_eq!(PathBuf::from(audio_uk), media_dir.join("apple.mp3"));
+            assert!(audio_us.is_none());
+            assert!(media_dir.join("apple.mp3").is_file());
+
+            let learning = sqlx::query(
+                r#"
+SELECT proficiency_score, learn_count, ease_factor, interval_days, lapses, last_learned_at, due_at
+FROM user_word_learning
+"#,
+            )
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to load learning row");
+            assert_eq!(learning.get::<i64, _>("proficiency_score"), 4);
+            assert_eq!(learning.get::<i64, _>("learn_count"), 4);
+            assert_eq!(learning.get::<f64, _>("ease_factor"), 2.3);
+            assert_eq!(learning.get::<f64, _>("interval_days"), 12.0);
+            assert_eq!(learning.get::<i64, _>("lapses"), 1);
+            assert_eq!(
+                learning.get::<String, _>("last_learned_at"),
+                "2023-12-14 22:13:20"
+            );
+            assert_eq!(learning.get::<String, _>("due_at"), "2023-12-14 22:13:20");
+
+            let _ = fs::remove_dir_all(&dir);
+        });
+    }
+
+    #[test]
+    fn exported_package_round_trips_words_schedule_and_media() {
+        async_runtime::block_on(async {
+            let dir = temp_dir("anki-export");
+            let pool = setup_pool().await;
+            let audio_path = dir.join("cached-apple.mp3");
+            fs::write(&audio_path, b"ID3").expect("Failed to write audio");
+            // Same file name in another folder must not be mistaken for it.
+            fs::create_dir_all(dir.join("us")).expect("Failed to create audio dir");
+            let other_audio_path = dir.join("us").join("cached-apple.mp3");
+            fs::write(&other_audio_path, b"US").expect("Failed to write audio");
+
+            let mut tx = pool.begin().await.expect("Failed to begin");
+            let target = word_bank::prepare_import_list(
+                &mut tx,
+                "cet4",
+                false,
+                &ImportSource {
+                    kind: "csv",
+                    name: None,
+                    merge_policy: MergePolicy::Overwrite,
+                },
+            )
+            .await
+            .expect("Failed to create list");
+            let apple_id = word_bank::upsert_dictionary_record(
+                &mut tx,
+                &target,
+                DictionaryRecord {
+                    word: "apple".to_string(),
+                    part_of_speech_and_meanings: Some("n. 苹果\nn. <苹果树>".to_string()),
+                    audio_uk: Some(audio_path.display().to_string()),
+                    audio_us: Some("https://example.com/apple.mp3".to_string()),
+                    ..DictionaryRecord::default()
+                },
+                MergePolicy::Overwrite,
+            )
+            .await
+            .expect("Failed to insert apple")
+            .expect("apple id")
+            .id;
+            word_bank::upsert_dictionary_record(
+                &mut tx,
+                &target,
+                DictionaryRecord {
+                    word: "banana".to_string(),
+                    audio_uk: Some(other_audio_path.display().to_string()),
+                    ..DictionaryRecord::default()
+                },
+                MergePolicy::Overwrite,
+            )
+            .await
+            .expect("Failed to insert banana");
+            // A later dictionary sharing the headword must not leak into the export.
+            let other = word_bank::prepare_import_list(
+                &mut tx,
+                "cet6",
+                false,
+                &ImportSource {
+                    kind: "csv",
+                    name: None,
+                    merge_policy: MergePolicy::Overwrite,
+                },
+            )
+            .await
+            .expect("Failed to create list");
+            word_bank::upsert_dictionary_record(
+                &mut tx,
+                &other,
+                DictionaryRecord {
+                    word: "apple".to_string(),
+                    part_of_speech_and_meanings: Some("n. 苹果公司".to_string()),
+                    ..DictionaryRecord::default()
+                },
+                MergePolicy::Overwrite,
+            )
+            .await
+            .expect("Failed to insert apple");
+            tx.commit().await.expect("Failed to commit");
+            sqlx::query(
+                r#"
+INSERT INTO user_word_learning (
+  word_id, proficiency_score, last_learned_at, learn_count, ease_factor, interval_days, repetitions, lapses, due_at
+)
+VALUES (?, 3, datetime('now', '-1 day'), 3, 2.6, 6, 3, 1, datetime('now', '+5 days'))
+"#,
+            )
+            .bind(apple_id)
+            .execute(&pool)
+            .await
+            .expect("Failed to insert progress");
+
+            let package_path = dir.join("cet4.apkg");
+            let summary = export_package_with_pool(&pool, target.list_id, &package_path)
+                .await
+                .expect("Failed to export");
+            assert_eq!(summary.note_count, 2);
+            assert_eq!(summary.media_count, 2);
+
+            let note_types = inspect_package(&package_path)
+                .await
+                .expect("Failed to inspect export");
+            assert_eq!(note_types[0].fields, EXPORT_FIELDS);
+
+            let imported = setup_pool().await;
+            let mapping = AnkiFieldMapping {
+                note_type: None,
+                word: "Word".to_string(),
+                phonetic: Some("Phonetic".to_string()),
+                part_of_speech_and_meanings: Some("Meanings".to_string()),
+                example_sentence: None,
+                example_translation: None,
+                audio_uk: Some("Audio UK".to_string()),
+                audio_us: Some("Audio US".to_string()),
+            };
+            let media_dir = dir.join(MEDIA_DIR_NAME);
+            let summary = import_package_with_pool(
+                &imported,
+                "round-trip",
+                &package_path,
+                &mapping,
+                &AnkiImportOptions {
+                    include_review_history: true,
+                    ..AnkiImportOptions::default()
+                },
+                false,
+                Some(&media_dir),
+            )
+            .await
+            .expect("Failed to import export");
+            assert_eq!(summary.upserted, 2);
+
+            let row = sqlx::query(
+                "SELECT part_of_speech_and_meanings, audio_uk, audio_us FROM word WHERE word = 'apple'",
+            )
+            .fetch_one(&imported)
+            .await
+            .expect("Failed to load apple");
+            assert_eq!(
+                row.get::<String, _>("part_of_speech_and_meanings"),
+                "n. 苹果\nn. <苹果树>"
+            );
+            assert_eq!(
+                PathBuf::from(row.get::<String, _>("audio_uk")),
+                media_dir.join("cached-apple.mp3")
+            );
+            assert_eq!(
+                row.get::<Option<String>, _>("audio_us").as_deref(),
+                Some("https://example.com/apple.mp3")
+            );
+            let banana_audio: String =
+                sqlx::query_scalar("SELECT audio_uk FROM word WHERE word = 'banana'")
+                    .fetch_one(&imported)
+                    .await
+                    .expect("Failed to load banana");
+            assert_eq!(fs::read(banana_audio).expect("banana audio"), b"US");
+
+            let learning = sqlx::query(
+                r#"
+SELECT w.word, uwl.interval_days, uwl.ease_factor, uwl.lapses,
+  CAST(julianday(uwl.due_at) - julianday('now', 'start of day') AS INTEGER) AS due_day
+FROM user_word_learning uwl
+JOIN word w ON w.id = uwl.word_id
+"#,
+            )
+            .fetch_all(&imported)
+            .await
+            .expect("Failed to load progress");
+            assert_eq!(learning.len(), 1);
+            assert_eq!(learning[0].get::<String, _>("word"), "apple");
+            assert_eq!(learning[0].get::<f64, _>("interval_days"), 6.0);
+            assert_eq!(learning[0].get::<f64, _>("ease_factor"), 2.6);
+            assert_eq!(learning[0].get::<i64, _>("lapses"), 1);
+            assert_eq!(learning[0].get::<i64, _>("due_day"), 5);
+
+            let _ = fs::remove_dir_all(&dir);
+        });
+    }
+}
@@ -0,0 +1,446 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+
+use crate::migrations;
+use crate::word_bank::{self, Database, StudyCalendarCache};
+
+const BACKUP_DIR_NAME: &str = "backups";
+const AUTOMATIC_BACKUP_PREFIX: &str = "auto-";
+const AUTOMATIC_BACKUP_KEEP: usize = 5;
+const REQUIRED_TABLES: [&str; 5] = [
+    "word_list",
+    "word",
+    "word_list_map",
+    "user_word_learning",
+    "study_log",
+];
+
+#[derive(Debug, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    pub schema_version: i64,
+}
+
+/// Writes a consistent snapshot of the live database with `VACUUM INTO`.
+async fn snapshot(pool: &SqlitePool, target: &Path) -> Result<BackupInfo> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建备份目录失败: {}", parent.display()))?;
+    }
+
+    // VACUUM INTO refuses to overwrite, so write beside the target and swap it in.
+    let staging = target.with_extension("partial");
+    if staging.exists() {
+        fs::remove_file(&staging)
+            .with_context(|| format!("清理临时备份失败: {}", staging.display()))?;
+    }
+    sqlx::query("VACUUM INTO ?")
+        .bind(staging.to_string_lossy().into_owned())
+        .execute(pool)
+        .await
+        .context("写入数据库备份失败")?;
+    fs::rename(&staging, target)
+        .with_context(|| format!("保存数据库备份失败: {}", target.display()))?;
+
+    let size_bytes = fs::metadata(target)
+        .with_context(|| format!("读取备份文件失败: {}", target.display()))?
+        .len();
+    Ok(BackupInfo {
+        path: target.display().to_string(),
+        size_bytes,
+        schema_version: migrations::read_version(pool).await?,
+    })
+}
+
+/// Checks that a file is an intact word-card database this build can open.
+async fn validate_backup(path: &Path) -> Result<i64> {
+    if !path.is_file() {
+        bail!("备份文件不存在: {}", path.display());
+    }
+
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .with_context(|| format!("无法打开备份文件: {}", path.display()))?;
+
+    let result = validate_backup_pool(&pool).await;
+    pool.close().await;
+    result
+}
+
+async fn validate_backup_pool(pool: &SqlitePool) -> Result<i64> {
+    let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(pool)
+        .await
+        .context("备份文件不是有效的 SQLite 数据库")?;
+    if integrity != "ok" {
+        bail!("备份文件已损坏: {integrity}");
+    }
+
+    let version = migrations::read_version(pool).await?;
+    if version > migrations::SCHEMA_VERSION {
+        bail!(
+            "备份文件版本为 {version}，高于当前应用支持的版本 {}，请升级应用后再恢复",
+            migrations::SCHEMA_VERSION
+        );
+    }
+
+    for table in REQUIRED_TABLES {
+        let exists: Option<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(table)
+                .fetch_optional(pool)
+                .await
+                .context("读取备份文件结构失败")?;
+        if exists.is_none() {
+            bail!("备份文件缺少数据表: {table}");
+        }
+    }
+
+    Ok(version)
+}
+
+fn backup_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(BACKUP_DIR_NAME)
+}
+
+/// Keeps a rotating snapshot before destructive operations.
+/// In-memory databases have nothing to back up and are skipped.
+pub async fn create_automatic_backup(db: &Database, reason: &str) -> Result<Option<BackupInfo>> {
+    let Some(db_path) = db.path() else {
+        return Ok(None);
+    };
+    let pool = db.pool()?;
+    let dir = backup_dir(db_path);
+
+    let stamp: String = sqlx::query_scalar("SELECT strftime('%Y%m%d-%H%M%S', 'now')")
+        .fetch_one(&pool)
+        .await
+        .context("生成备份时间失败")?;
+    // Names are `auto-<stamp>-<sequence>-<reason>` with fixed-width stamp and
+    // sequence, so sorting them by name sorts them by creation order.
+    let prefix = format!("{AUTOMATIC_BACKUP_PREFIX}{stamp}-");
+    let mut sequence = automatic_backups(&dir)?
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str()?.strip_prefix(&prefix)?.get(..3))
+        .filter_map(|sequence| sequence.parse::<u32>().ok())
+        .max()
+        .map_or(0, |sequence| sequence + 1);
+    let mut target = dir.join(format!("{prefix}{sequence:03}-{reason}.sqlite3"));
+    while target.exists() {
+        sequence += 1;
+        target = dir.join(format!("{prefix}{sequence:03}-{reason}.sqlite3"));
+    }
+
+    let info = snapshot(&pool, &target).await?;
+    prune_automatic_backups(&dir)?;
+    Ok(Some(info))
+}
+
+/// Lists automatic backups oldest first.
+fn automatic_backups(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("读取备份目录失败: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(AUTOMATIC_BACKUP_PREFIX) && name.ends_with(".sqlite3")
+                })
+        })
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+fn prune_automatic_backups(dir: &Path) -> Result<()> {
+    let backups = automatic_backups(dir)?;
+    let excess = backups.len().saturating_sub(AUTOMATIC_BACKUP_KEEP);
+    for path in backups.into_iter().take(excess) {
+        fs::remove_file(&path).with_context(|| format!("清理旧备份失败: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Resolves `path` for comparison, following symlinks and `..`; a file that
+/// does not exist yet is resolved through its parent directory.
+fn comparable_path(path: &Path) -> PathBuf {
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.to_path_buf();
+    };
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+    fs::canonicalize(parent).map_or_else(|_| path.to_path_buf(), |parent| parent.join(name))
+}
+
+fn is_same_file(left: &Path, right: &Path) -> bool {
+    comparable_path(left) == comparable_path(right)
+}
+
+pub async fn backup_database(db: &Database, path: &str) -> Result<BackupInfo> {
+    let target = PathBuf::from(path.trim());
+    if target.as_os_str().is_empty() {
+        bail!("备份路径不能为空");
+    }
+    if db
+        .path()
+        .is_some_and(|db_path| is_same_file(db_path, &target))
+    {
+        bail!("备份路径不能与当前数据库相同");
+    }
+    let pool = db.pool()?;
+    snapshot(&pool, &target).await
+}
+
+pub async fn restore_database(
+    db: &Database,
+    cache: &StudyCalendarCache,
+    path: &str,
+) -> Result<BackupInfo> {
+    let source = PathBuf::from(path.trim());
+    let Some(db_path) = db.path().map(Path::to_path_buf) else {
+        bail!("当前数据库不支持恢复");
+    };
+    if is_same_file(&source, &db_path) {
+        bail!("备份路径不能与当前数据库相同");
+    }
+    validate_backup(&source).await?;
+    let Some(previous) = create_automatic_backup(db, "before-restore").await? else {
+        bail!("当前数据库不支持恢复");
+    };
+
+    if let Some(pool) = db.take() {
+        pool.close().await;
+    }
+    let error = match install_backup(&source, &db_path).await {
+        Ok((pool, info)) => {
+            db.set(pool);
+            cache.invalidate();
+            return Ok(info);
+        }
+        Err(error) => error,
+    };
+
+    // Put the pre-restore snapshot back so the app keeps a working database.
+    let reverted = replace_database_file(Path::new(&previous.path), &db_path);
+    let pool = word_bank::open_pool(&db_path)
+        .await
+        .with_context(|| format!("恢复失败且无法重新打开原数据库: {error:#}"))?;
+    db.set(pool);
+    match reverted {
+        Ok(()) => Err(error),
+        Err(revert_error) => Err(error.context(format!("还原原数据库失败: {revert_error:#}"))),
+    }
+}
+
+/// Swaps the backup in and opens it; the pool is closed again on failure.
+async fn install_backup(source: &Path, db_path: &Path) -> Result<(SqlitePool, BackupInfo)> {
+    replace_database_file(source, db_path)?;
+    let pool = word_bank::open_pool(db_path).await?;
+    match describe_restored(&pool, db_path).await {
+        Ok(info) => Ok((pool, info)),
+        Err(error) => {
+            pool.close().await;
+            Err(error)
+        }
+    }
+}
+
+async fn describe_restored(pool: &SqlitePool, db_path: &Path) -> Result<BackupInfo> {
+    word_bank::ensure_schema(pool).await?;
+    word_bank::sync_fuzzy_word_list(pool).await?;
+    let size_bytes = fs::metadata(db_path)
+        .with_context(|| format!("读取数据库文件失败: {}", db_path.display()))?
+        .len();
+    Ok(BackupInfo {
+        path: db_path.display().to_string(),
+        size_bytes,
+        schema_version: migrations::read_version(pool).await?,
+    })
+}
+
+/// Copies the source beside the database first, so a failed copy never
+/// leaves a truncated database in place.
+fn replace_database_file(source: &Path, db_path: &Path) -> Result<()> {
+    let staging = db_path.with_extension("restoring");
+    if let Err(error) = fs::copy(source, &staging) {
+        let _ = fs::remove_file(&staging);
+        return Err(error).with_context(|| format!("恢复数据库失败: {}", source.display()));
+    }
+
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        let sidecar = PathBuf::from(sidecar);
+        if sidecar.exists() {
+            fs::remove_file(&sidecar)
+                .with_context(|| format!("清理数据库日志失败: {}", sidecar.display()))?;
+        }
+    }
+    fs::rename(&staging, db_path)
+        .with_context(|| format!("恢复数据库失败: {}", db_path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::async_runtime;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "word-card-{name}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos())
+                .unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).expect("Failed to create temp dir");
+        dir
+    }
+
+    async fn open_database(dir: &Path) -> Database {
+        let db_path = dir.join("word-card.sqlite3");
+        let pool = word_bank::open_pool(&db_path)
+            .await
+            .expect("Failed to open database");
+        word_bank::ensure_schema(&pool)
+            .await
+            .expect("Failed to migrate database");
+        let db = Database::default();
+        db.set_path(db_path);
+        db.set(pool);
+        db
+    }
+
+    async fn word_list_count(db: &Database) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(1) FROM word_list")
+            .fetch_one(&db.pool().expect("pool"))
+            .await
+            .expect("Failed to count word lists")
+    }
+
+    #[test]
+    fn backup_and_restore_round_trip() {
+        async_runtime::block_on(async {
+            let dir = temp_dir("restore");
+            let db = open_database(&dir).await;
+            let cache = StudyCalendarCache::default();
+            word_bank::create_word_list(&db, "list-a")
+                .await
+                .expect("Failed to create list");
+
+            let snapshot_path = dir.join("manual.sqlite3");
+            let info = backup_database(&db, &snapshot_path.to_string_lossy())
+                .await
+                .expect("Failed to back up");
+            assert!(info.size_bytes > 0);
+            assert_eq!(info.schema_version, migrations::SCHEMA_VERSION);
+
+            word_bank::create_word_list(&db, "list-b")
+                .await
+                .expect("Failed to create list");
+            assert_eq!(word_list_count(&db).await, 2);
+
+            restore_database(&db, &cache, &snapshot_path.to_string_lossy())
+                .await
+                .expect("Failed to restore");
+            assert_eq!(word_list_count(&db).await, 1);
+
+            let automatic: Vec<_> = fs::read_dir(dir.join(BACKUP_DIR_NAME))
+                .expect("Failed to read backups")
+                .collect();
+            assert_eq!(automatic.len(), 1);
+
+            let _ = fs::remove_dir_all(&dir);
+        });
+    }
+
+    #[test]
+    fn restore_rejects_invalid_files_and_rotation_keeps_recent() {
+        async_runtime::block_on(async {
+            let dir = temp_dir("invalid");
+            let db = open_database(&dir).await;
+            let cache = StudyCalendarCache::default();
+
+            fs::create_dir(dir.join("sub")).expect("Failed to create sub dir");
+            let live = dir.join("sub").join("..").join("word-card.sqlite3");
+            assert!(backup_database(&db, &live.to_string_lossy()).await.is_err());
+            assert!(restore_database(&db, &cache, &live.to_string_lossy())
+                .await
+                .is_err());
+
+            let bogus = dir.join("bogus.sqlite3");
+            fs::write(&bogus, "not a database").expect("Failed to write bogus file");
+            assert!(restore_database(&db, &cache, &bogus.to_string_lossy())
+                .await
+                .is_err());
+            assert!(!dir.join(BACKUP_DIR_NAME).exists());
+
+            let mut created = Vec::new();
+            for reason in ["b", "a", "b", "a", "b", "a", "b"] {
+                let info = create_automatic_backup(&db, reason)
+                    .await
+                    .expect("Failed to create automatic backup")
+                    .expect("File databases are backed up");
+                created.push(PathBuf::from(info.path));
+            }
+            let kept = automatic_backups(&dir.join(BACKUP_DIR_NAME)).expect("Failed to list");
+            assert_eq!(kept, created[created.len() - AUTOMATIC_BACKUP_KEEP..]);
+
+            let _ = fs::remove_dir_all(&dir);
+        });
+    }
+
+    #[test]
+    fn failed_restore_keeps_the_current_database() {
+        async_runtime::block_on(async {
+            let dir = temp_dir("failed-restore");
+            let db = open_database(&dir).await;
+            let cache = StudyCalendarCache::default();
+            word_bank::create_word_list(&db, "list-a")
+                .await
+                .expect("Failed to create list");
+            let snapshot_path = dir.join("manual.sqlite3");
+            backup_database(&db, &snapshot_path.to_string_lossy())
+                .await
+                .expect("Failed to back up");
+            word_bank::create_word_list(&db, "list-b")
+                .await
+                .expect("Failed to create list");
+
+            // A directory in the staging spot makes the copy fail.
+            fs::create_dir(dir.join("word-card.restoring")).expect("Failed to block staging");
+            assert!(
+                restore_database(&db, &cache, &snapshot_path.to_string_lossy())
+                    .await
+                    .is_err()
+            );
+            assert_eq!(word_list_count(&db).await, 2);
+
+            let _ = fs::remove_dir_all(&dir);
+        });
+    }
+}
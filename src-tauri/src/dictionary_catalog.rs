@@ -0,0 +1,678 @@
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager};
+
+const BUNDLED_MANIFEST: &str = include_str!("../dictionary-catalog.json");
+const MANIFEST_FILE_NAME: &str = "dictionary-catalog.json";
+const MIRROR_FILE_NAME: &str = "dictionary-mirror.txt";
+const DOWNLOAD_DIR_NAME: &str = "dictionaries";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "dictionary-download-progress";
+
+/// One downloadable dictionary in the catalog manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    /// Word list the dictionary is imported into.
+    pub name: String,
+    pub version: String,
+    pub url: String,
+    /// File name looked up in a mirror; defaults to the last segment of `url`.
+    #[serde(default)]
+    pub file_name: Option<String>,
+    /// Expected SHA-256 in hex. Without it a download is only resumed when the
+    /// server can confirm the file has not changed.
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub license: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CatalogManifest {
+    /// Local directory or HTTP base URL that serves the catalog files instead of
+    /// their own URLs, for offline or air-gapped installs.
+    pub mirror: Option<String>,
+    pub dictionaries: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DownloadedDictionary {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    /// Whether an interrupted download was continued instead of restarted.
+    pub resumed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub id: String,
+    pub received: u64,
+    pub total: Option<u64>,
+}
+
+/// Where an entry's file is read from once the mirror is applied.
+#[derive(Debug, PartialEq)]
+enum DownloadSource {
+    Http(String),
+    Local(PathBuf),
+}
+
+fn is_http(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+fn parse_manifest(content: &str) -> Result<CatalogManifest> {
+    let manifest: CatalogManifest = serde_json::from_str(content).context("词库目录格式不正确")?;
+    for (index, entry) in manifest.dictionaries.iter().enumerate() {
+        if entry.id.trim().is_empty() || entry.name.trim().is_empty() {
+            bail!("词库目录第 {} 项缺少 id 或名称", index + 1);
+        }
+        if manifest.dictionaries[..index]
+            .iter()
+            .any(|other| other.id == entry.id)
+        {
+            bail!("词库目录中存在重复的 id: {}", entry.id);
+        }
+    }
+    Ok(manifest)
+}
+
+fn app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf> {
+    app.path().app_data_dir().context("解析应用数据目录失败")
+}
+
+/// Layers the user's manifest over the bundled one: entries with the same id
+/// replace the bundled entry and new ids are added after it.
+fn merge_manifest(mut bundled: CatalogManifest, overlay: CatalogManifest) -> CatalogManifest {
+    for entry in overlay.dictionaries {
+        match bundled
+            .dictionaries
+            .iter_mut()
+            .find(|bundled| bundled.id == entry.id)
+        {
+            Some(bundled) => *bundled = entry,
+            None => bundled.dictionaries.push(entry),
+        }
+    }
+    bundled.mirror = overlay.mirror.or(bundled.mirror);
+    bundled
+}
+
+/// Builds the catalog from the bundled manifest, so app updates always reach
+/// the user, plus the optional user manifest and the saved mirror in `dir`.
+fn load_manifest(dir: &Path) -> Result<CatalogManifest> {
+    let mut manifest = parse_manifest(BUNDLED_MANIFEST)?;
+    let overlay_path = dir.join(MANIFEST_FILE_NAME);
+    if overlay_path.is_file() {
+        let content = fs::read_to_string(&overlay_path)
+            .with_context(|| format!("读取词库目录失败: {}", overlay_path.display()))?;
+        manifest = merge_manifest(manifest, parse_manifest(&content)?);
+    }
+
+    let mirror_path = dir.join(MIRROR_FILE_NAME);
+    if mirror_path.is_file() {
+        let mirror = fs::read_to_string(&mirror_path)
+            .with_context(|| format!("读取词库镜像设置失败: {}", mirror_path.display()))?;
+        manifest.mirror = Some(mirror.trim().to_string()).filter(|mirror| !mirror.is_empty());
+    }
+    Ok(manifest)
+}
+
+pub fn get_dictionary_catalog(app: &tauri::AppHandle) -> Result<CatalogManifest> {
+    load_manifest(&app_data_dir(app)?)
+}
+
+/// Points the catalog at a mirror, or back at the upstream URLs when empty.
+/// Only the mirror is saved; the entries keep coming from the manifests.
+pub fn set_dictionary_mirror(
+    app: &tauri::AppHandle,
+    mirror: Option<&str>,
+) -> Result<CatalogManifest> {
+    let dir = app_data_dir(app)?;
+    let mirror = mirror.map(str::trim).unwrap_or_default();
+    fs::create_dir_all(&dir).with_context(|| format!("创建应用数据目录失败: {}", dir.display()))?;
+    let path = dir.join(MIRROR_FILE_NAME);
+    fs::write(&path, mirror)
+        .with_context(|| format!("保存词库镜像设置失败: {}", path.display()))?;
+    load_manifest(&dir)
+}
+
+fn entry_file_name(entry: &CatalogEntry) -> String {
+    entry
+        .file_name
+        .clone()
+        .or_else(|| {
+            entry
+                .url
+                .rsplit('/')
+                .next()
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| format!("{}.csv", entry.id))
+}
+
+fn resolve_source(entry: &CatalogEntry, mirror: Option<&str>) -> DownloadSource {
+    match mirror.map(str::trim).filter(|mirror| !mirror.is_empty()) {
+        Some(mirror) if is_http(mirror) => DownloadSource::Http(format!(
+            "{}/{}",
+            mirror.trim_end_matches('/'),
+            entry_file_name(entry)
+        )),
+        Some(mirror) => DownloadSource::Local(Path::new(mirror).join(entry_file_name(entry))),
+        None if is_http(&entry.url) => DownloadSource::Http(entry.url.clone()),
+        None => DownloadSource::Local(PathBuf::from(
+            entry.url.strip_prefix("file://").unwrap_or(&entry.url),
+        )),
+    }
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("读取下载文件失败: {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("读取下载文件失败: {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Runs file work off the async runtime, so hashing or copying a large
+/// dictionary does not stall other tasks.
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .context("后台文件任务失败")?
+}
+
+/// Checks the size and checksum the catalog promises; returns the file's SHA-256.
+fn verify_download(entry: &CatalogEntry, path: &Path) -> Result<String> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("读取下载文件失败: {}", path.display()))?
+        .len();
+    if let Some(expected) = entry.size.filter(|expected| *expected != size) {
+        bail!(
+            "{} 文件大小不符：应为 {expected} 字节，实际 {size} 字节",
+            entry.name
+        );
+    }
+    let actual = sha256_file(path)?;
+    if let Some(expected) = &entry.sha256 {
+        if !expected.trim().eq_ignore_ascii_case(&actual) {
+            bail!("{} 校验失败，文件可能已损坏或被篡改", entry.name);
+        }
+    }
+    Ok(actual)
+}
+
+/// Where the ETag or Last-Modified of a partial download is kept.
+fn validator_path(partial: &Path) -> PathBuf {
+    let mut path = OsString::from(partial.as_os_str());
+    path.push(".validator");
+    PathBuf::from(path)
+}
+
+/// Picks the response header that can later prove the file is unchanged.
+/// Weak ETags are not allowed in `If-Range`.
+fn response_validator(response: &reqwest::Response) -> Option<String> {
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    header(ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(LAST_MODIFIED))
+}
+
+/// Downloads `url` into `partial`, continuing from its current length when
+/// that is safe: the entry has a pinned checksum, or the server confirms with
+/// `If-Range` that the file has not changed. Returns whether it was resumed.
+async fn fetch_http(
+    url: &str,
+    partial: &Path,
+    pinned: bool,
+    on_progress: &(dyn Fn(u64, Option<u64>) + Send + Sync),
+) -> Result<bool> {
+    let validator_path = validator_path(partial);
+    let validator = fs::read_to_string(&validator_path)
+        .ok()
+        .map(|validator| validator.trim().to_string())
+        .filter(|validator| !validator.is_empty());
+    let mut offset = fs::metadata(partial).map_or(0, |metadata| metadata.len());
+    // Nothing could tell whether the partial file still matches upstream.
+    if !pinned && validator.is_none() {
+        offset = 0;
+    }
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .context("创建下载客户端失败")?;
+    let mut request = client.get(url);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={offset}-"));
+        if let Some(validator) = &validator {
+            request = request.header(IF_RANGE, validator);
+        }
+    }
+    let mut response = request
+        .send()
+        .await
+        .with_context(|| format!("连接下载地址失败: {url}"))?;
+
+    let status = response.status();
+    if offset > 0 && status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // Only a partial file as long as the whole upstream file is complete.
+        let length = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|range| range.strip_prefix("bytes */"))
+            .and_then(|length| length.trim().parse::<u64>().ok());
+        if length == Some(offset) {
+            return Ok(true);
+        }
+        let _ = fs::remove_file(partial);
+        let _ = fs::remove_file(&validator_path);
+        bail!("下载文件与服务器不一致，请重试");
+    }
+    if !status.is_success() {
+        bail!("下载失败: HTTP {status}");
+    }
+
+    let resumed = offset > 0 && status == StatusCode::PARTIAL_CONTENT;
+    if !resumed {
+        match response_validator(&response) {
+            Some(validator) => fs::write(&validator_path, validator)
+                .with_context(|| format!("写入下载文件失败: {}", validator_path.display()))?,
+            None => {
+                let _ = fs::remove_file(&validator_path);
+            }
+        }
+    }
+    let mut received = if resumed { offset } else { 0 };
+    let total = response.content_length().map(|length| length + received);
+    let mut file = if resumed {
+        OpenOptions::new().append(true).open(partial)
+    } else {
+        File::create(partial)
+    }
+    .with_context(|| format!("写入下载文件失败: {}", partial.display()))?;
+
+    on_progress(received, total);
+    while let Some(chunk) = response.chunk().await.context("下载中断")? {
+        file.write_all(&chunk)
+            .with_context(|| format!("写入下载文件失败: {}", partial.display()))?;
+        received += chunk.len() as u64;
+        on_progress(received, total);
+    }
+    file.flush()
+        .with_context(|| format!("写入下载文件失败: {}", partial.display()))?;
+    Ok(resumed)
+}
+
+/// Fetches a catalog entry into `dir`, keeping interrupted downloads as
+/// `.part` files so the next attempt can resume them.
+pub(crate) async fn download_entry(
+    entry: &CatalogEntry,
+    mirror: Option<&str>,
+    dir: &Path,
+    on_progress: &(dyn Fn(u64, Option<u64>) + Send + Sync),
+) -> Result<DownloadedDictionary> {
+    fs::create_dir_all(dir).with_context(|| format!("创建下载目录失败: {}", dir.display()))?;
+    let file_name = entry_file_name(entry);
+    let extension = Path::new(&file_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("csv");
+    let target = dir.join(format!("{}-{}.{extension}", entry.id, entry.version));
+    let partial = dir.join(format!("{}-{}.{extension}.part", entry.id, entry.version));
+    let downloaded = |sha256: String, resumed: bool| -> Result<DownloadedDictionary> {
+        let size_bytes = fs::metadata(&target)
+            .with_context(|| format!("读取下载文件失败: {}", target.display()))?
+            .len();
+        Ok(DownloadedDictionary {
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            version: entry.version.clone(),
+            path: target.display().to_string(),
+            size_bytes,
+            sha256,
+            resumed,
+        })
+    };
+
+    let verify = |path: PathBuf| {
+        let entry = entry.clone();
+        run_blocking(move || verify_download(&entry, &path))
+    };
+
+    // Only a pinned checksum proves an earlier download is still current.
+    if entry.sha256.is_some() && target.is_file() {
+        if let Ok(sha256) = verify(target.clone()).await {
+            let size = fs::metadata(&target).map_or(0, |metadata| metadata.len());
+            on_progress(size, Some(size));
+            return downloaded(sha256, false);
+        }
+    }
+
+    let resumed = match resolve_source(entry, mirror) {
+        DownloadSource::Http(url) => {
+            fetch_http(&url, &partial, entry.sha256.is_some(), on_progress).await?
+        }
+        DownloadSource::Local(source) => {
+            let copy_target = partial.clone();
+            run_blocking(move || {
+                fs::copy(&source, &copy_target)
+                    .with_context(|| format!("读取本地词库失败: {}", source.display()))
+            })
+            .await?;
+            let size = fs::metadata(&partial).map_or(0, |metadata| metadata.len());
+            on_progress(size, Some(size));
+            false
+        }
+    };
+
+    let validator = validator_path(&partial);
+    let sha256 = match verify(partial.clone()).await {
+        Ok(sha256) => sha256,
+        Err(error) => {
+            let _ = fs::remove_file(&partial);
+            let _ = fs::remove_file(&validator);
+            return Err(error);
+        }
+    };
+    fs::rename(&partial, &target)
+        .with_context(|| format!("保存下载文件失败: {}", target.display()))?;
+    let _ = fs::remove_file(&validator);
+    downloaded(sha256, resumed)
+}
+
+/// Downloads a catalog dictionary, emitting [`DOWNLOAD_PROGRESS_EVENT`] as
+/// bytes arrive. The returned path can be passed to `import_dictionary_file`.
+pub async fn download_dictionary(app: &tauri::AppHandle, id: &str) -> Result<DownloadedDictionary> {
+    let dir = app_data_dir(app)?;
+    let manifest = load_manifest(&dir)?;
+    let Some(entry) = manifest.dictionaries.iter().find(|entry| entry.id == id) else {
+        bail!("词库目录中不存在: {id}");
+    };
+
+    let on_progress = |received, total| {
+        let _ = app.emit(
+            DOWNLOAD_PROGRESS_EVENT,
+            DownloadProgress {
+                id: entry.id.clone(),
+                received,
+                total,
+            },
+        );
+    };
+    download_entry(
+        entry,
+        manifest.mirror.as_deref(),
+        &dir.join(DOWNLOAD_DIR_NAME),
+        &on_progress,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use tauri::async_runtime;
+
+    const CONTENT: &[u8] = b"word,meaning\napple,n. apple\nbanana,n. banana\n";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "word-card-{name}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos())
+                .unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).expect("Failed to create temp dir");
+        dir
+    }
+
+    fn entry(url: &str, sha256: Option<String>) -> CatalogEntry {
+        CatalogEntry {
+            id: "cet4".to_string(),
+            name: "CET4".to_string(),
+            version: "1".to_string(),
+            url: url.to_string(),
+            file_name: Some("CET-4.csv".to_string()),
+            sha256,
+            size: Some(CONTENT.len() as u64),
+            license: None,
+        }
+    }
+
+    fn content_sha256() -> String {
+        format!("{:x}", Sha256::digest(CONTENT))
+    }
+
+    const ETAG_VALUE: &str = "\"v1\"";
+
+    /// Serves `CONTENT` once with an ETag, honouring a `Range: bytes=N-` header
+    /// unless an `If-Range` names another version.
+    fn serve_once() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+        let address = listener.local_addr().expect("Failed to read address");
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("Failed to accept");
+            let mut reader = BufReader::new(stream.try_clone().expect("Failed to clone"));
+            let mut offset = 0;
+            let mut current = true;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("Failed to read request");
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(range) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                    offset = range.trim().trim_end_matches('-').parse().unwrap_or(0);
+                }
+                if let Some(validator) = line.to_ascii_lowercase().strip_prefix("if-range:") {
+                    current = validator.trim() == ETAG_VALUE;
+                }
+            }
+            let (status, body) = if offset > 0 && current {
+                ("206 Partial Content", &CONTENT[offset..])
+            } else {
+                ("200 OK", CONTENT)
+            };
+            let header = format!(
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nETag: {ETAG_VALUE}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream
+                .write_all(header.as_bytes())
+                .and_then(|_| stream.write_all(body))
+                .expect("Failed to respond");
+        });
+        format!("http://{address}")
+    }
+
+    #[test]
+    fn bundled_manifest_lists_cet_dictionaries_and_mirrors_resolve() {
+        let manifest = parse_manifest(BUNDLED_MANIFEST).expect("Failed to parse manifest");
+        let ids: Vec<&str> = manifest
+            .dictionaries
+            .iter()
+            .map(|entry| entry.id.as_str())
+            .collect();
+        assert_eq!(ids, ["cet4", "cet6"]);
+        assert!(parse_manifest(r#"{"dictionaries":[{"id":"a","name":"A","version":"1","url":"x"},{"id":"a","name":"B","version":"1","url":"y"}]}"#).is_err());
+
+        let cet4 = &manifest.dictionaries[0];
+        assert_eq!(
+            resolve_source(cet4, None),
+            DownloadSource::Http(cet4.url.clone())
+        );
+        assert_eq!(
+            resolve_source(cet4, Some("http://10.0.0.2:8080/dicts/")),
+            DownloadSource::Http("http://10.0.0.2:8080/dicts/CET-4.csv".to_string())
+        );
+        assert_eq!(
+            resolve_source(cet4, Some("/srv/dicts")),
+            DownloadSource::Local(PathBuf::from("/srv/dicts/CET-4.csv"))
+        );
+
+        let overlay = parse_manifest(r#"{"mirror":"/srv/dicts","dictionaries":[{"id":"cet6","name":"CET6","version":"2","url":"y"},{"id":"ielts","name":"IELTS","version":"1","url":"z"}]}"#)
+            .expect("Failed to parse overlay");
+        let merged = merge_manifest(manifest.clone(), overlay);
+        let versions: Vec<(&str, &str)> = merged
+            .dictionaries
+            .iter()
+            .map(|entry| (entry.id.as_str(), entry.version.as_str()))
+            .collect();
+        assert_eq!(
+            versions,
+            [
+                ("cet4", manifest.dictionaries[0].version.as_str()),
+                ("cet6", "2"),
+                ("ielts", "1")
+            ]
+        );
+        assert_eq!(merged.mirror.as_deref(), Some("/srv/dicts"));
+    }
+
+    #[test]
+    fn downloads_verify_checksums_and_resume_partial_files() {
+        async_runtime::block_on(async {
+            let dir = temp_dir("catalog");
+            let mirror = dir.join("mirror");
+            fs::create_dir_all(&mirror).expect("Failed to create mirror");
+            fs::write(mirror.join("CET-4.csv"), CONTENT).expect("Failed to write mirror file");
+            let downloads = dir.join("downloads");
+            let mirror_text = mirror.to_string_lossy().into_owned();
+
+            let tampered = entry("unused", Some("0".repeat(64)));
+            assert!(
+                download_entry(&tampered, Some(&mirror_text), &downloads, &|_, _| {})
+                    .await
+                    .is_err()
+            );
+            assert!(!downloads.join("cet4-1.csv.part").exists());
+
+            let pinned = entry("unused", Some(content_sha256()));
+            let local = download_entry(&pinned, Some(&mirror_text), &downloads, &|_, _| {})
+                .await
+                .expect("Failed to copy from mirror");
+            assert_eq!(local.sha256, content_sha256());
+            assert_eq!(fs::read(&local.path).expect("downloaded file"), CONTENT);
+            fs::remove_file(&local.path).expect("Failed to remove download");
+
+            fs::write(downloads.join("cet4-1.csv.part"), &CONTENT[..10])
+                .expect("Failed to write partial file");
+            let progress = Mutex::new(Vec::new());
+            let base = serve_once();
+            let resumed = download_entry(
+                &entry(&format!("{base}/CET-4.csv"), Some(content_sha256())),
+                None,
+                &downloads,
+                &|received, total| progress.lock().unwrap().push((received, total)),
+            )
+            .await
+            .expect("Failed to resume download");
+            assert!(resumed.resumed);
+            assert_eq!(fs::read(&resumed.path).expect("downloaded file"), CONTENT);
+            let total = Some(CONTENT.len() as u64);
+            let progress = progress.into_inner().unwrap();
+            assert_eq!(progress.first(), Some(&(10, total)));
+            assert_eq!(progress.last(), Some(&(CONTENT.len() as u64, total)));
+
+            let _ = fs::remove_dir_all(&dir);
+        });
+    }
+
+    #[test]
+    fn unpinned_downloads_resume_only_when_the_server_confirms_the_file() {
+        async_runtime::block_on(async {
+            let dir = temp_dir("catalog-unpinned");
+            let partial = dir.join("cet4-1.csv.part");
+            let validator = validator_path(&partial);
+
+            // A partial file with nothing to check it against is discarded.
+            fs::write(&partial, b"stale").expect("Failed to write partial file");
+            let base = serve_once();
+            let fresh = download_entry(
+                &entry(&format!("{base}/CET-4.csv"), None),
+                None,
+                &dir,
+                &|_, _| {},
+            )
+            .await
+            .expect("Failed to download");
+            assert!(!fresh.resumed);
+            assert_eq!(fs::read(&fresh.path).expect("downloaded file"), CONTENT);
+            assert!(!validator.exists());
+
+            // A changed ETag makes the server send the whole file again.
+            fs::write(&partial, b"stale").expect("Failed to write partial file");
+            fs::write(&validator, "\"v0\"").expect("Failed to write validator");
+            let base = serve_once();
+            let changed = download_entry(
+                &entry(&format!("{base}/CET-4.csv"), None),
+                None,
+                &dir,
+                &|_, _| {},
+            )
+            .await
+            .expect("Failed to download");
+            assert!(!changed.resumed);
+            assert_eq!(fs::read(&changed.path).expect("downloaded file"), CONTENT);
+
+            // A matching ETag continues where the partial file stopped.
+            fs::write(&partial, &CONTENT[..10]).expect("Failed to write partial file");
+            fs::write(&validator, ETAG_VALUE).expect("Failed to write validator");
+            let base = serve_once();
+            let resumed = download_entry(
+                &entry(&format!("{base}/CET-4.csv"), None),
+                None,
+                &dir,
+                &|_, _| {},
+            )
+            .await
+            .expect("Failed to resume download");
+            assert!(resumed.resumed);
+            assert_eq!(fs::read(&resumed.path).expect("downloaded file"), CONTENT);
+
+            let _ = fs::remove_dir_all(&dir);
+        });
+    }
+}
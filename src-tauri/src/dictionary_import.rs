@@ -0,0 +1,1648 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Context, Result};
+use calamine::{open_workbook_auto, Data, Reader};
+use encoding_rs::{Encoding, GB18030, UTF_16BE, UTF_16LE, UTF_8};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{Emitter, Manager};
+
+use crate::backup;
+use crate::dictionary_catalog::run_blocking;
+use crate::word_bank::{
+    self, Database, DictionaryRecord, ImportRowReport, ImportRowStatus, ImportSource,
+    ImportSummary, ImportTarget, MergePolicy,
+};
+
+const PREVIEW_ROW_LIMIT: usize = 10;
+const DELIMITER_CANDIDATES: [u8; 3] = [b',', b'\t', b';'];
+const ENCODING_SNIFF_LIMIT: usize = 4096;
+/// Rows parsed before each batched write and progress report.
+const IMPORT_BATCH_ROWS: usize = 500;
+/// Bytes read up front from a file to detect its encoding and layout.
+const FILE_SAMPLE_BYTES: usize = 64 * 1024;
+const SPREADSHEET_EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+pub const IMPORT_PROGRESS_EVENT: &str = "dictionary-import-progress";
+
+/// The lower-cased spreadsheet extension of `path`, recorded as the import's
+/// source kind; `None` for anything that is not a spreadsheet.
+fn spreadsheet_kind(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?;
+    SPREADSHEET_EXTENSIONS
+        .into_iter()
+        .find(|candidate| extension.eq_ignore_ascii_case(candidate))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvDelimiter {
+    #[default]
+    Auto,
+    Comma,
+    Tab,
+    Semicolon,
+}
+
+impl CsvDelimiter {
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            b'\t' => CsvDelimiter::Tab,
+            b';' => CsvDelimiter::Semicolon,
+            _ => CsvDelimiter::Comma,
+        }
+    }
+
+    fn byte(self) -> Option<u8> {
+        match self {
+            CsvDelimiter::Auto => None,
+            CsvDelimiter::Comma => Some(b','),
+            CsvDelimiter::Tab => Some(b'\t'),
+            CsvDelimiter::Semicolon => Some(b';'),
+        }
+    }
+}
+
+/// Zero-based column index for each word field; only `word` is required.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub word: usize,
+    pub phonetic: Option<usize>,
+    pub part_of_speech_and_meanings: Option<usize>,
+    pub example_sentence: Option<usize>,
+    pub example_translation: Option<usize>,
+    pub audio_uk: Option<usize>,
+    pub audio_us: Option<usize>,
+}
+
+impl ColumnMapping {
+    fn last_column(&self) -> usize {
+        [
+            self.phonetic,
+            self.part_of_speech_and_meanings,
+            self.example_sentence,
+            self.example_translation,
+            self.audio_uk,
+            self.audio_us,
+        ]
+        .into_iter()
+        .flatten()
+        .fold(self.word, usize::max)
+    }
+}
+
+/// How to read a dictionary file. Anything left unset is detected from the content.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CsvImportOptions {
+    pub delimiter: CsvDelimiter,
+    pub has_header: Option<bool>,
+    pub mapping: Option<ColumnMapping>,
+    /// Parse and diff the file without keeping any changes.
+    pub dry_run: bool,
+    /// Skip malformed rows and report them instead of aborting the import.
+    pub lenient: bool,
+    pub merge_policy: MergePolicy,
+    /// Encoding label such as `gbk` for byte input; detected when unset.
+    pub encoding: Option<String>,
+    /// Worksheet to read from a spreadsheet; the first one when unset.
+    pub sheet: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CsvPreview {
+    pub delimiter: CsvDelimiter,
+    pub has_header: bool,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub mapping: Option<ColumnMapping>,
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpreadsheetPreview {
+    pub sheets: Vec<String>,
+    pub sheet: String,
+    pub has_header: bool,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub mapping: Option<ColumnMapping>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    pub token: String,
+    pub processed: u64,
+    pub total: u64,
+}
+
+/// Cancellation flags for running file imports, keyed by the caller's token.
+#[derive(Default)]
+pub struct ImportCancellation {
+    flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl ImportCancellation {
+    fn register(&self, token: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Ok(mut flags) = self.flags.lock() {
+            flags.insert(token.to_string(), flag.clone());
+        }
+        flag
+    }
+
+    fn finish(&self, token: &str) {
+        if let Ok(mut flags) = self.flags.lock() {
+            flags.remove(token);
+        }
+    }
+
+    /// Asks a running import to stop; returns whether one was found.
+    pub fn cancel(&self, token: &str) -> bool {
+        let Ok(flags) = self.flags.lock() else {
+            return false;
+        };
+        match flags.get(token) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Text decoded from a dictionary file and the encoding it was read as.
+pub(crate) struct DecodedText {
+    pub(crate) text: String,
+    pub(crate) encoding: &'static Encoding,
+    /// Name reported to the user; GB18030 text without four-byte sequences is GBK.
+    pub(crate) encoding_name: &'static str,
+}
+
+/// Resolved header and column settings shared by preview and import.
+struct TableLayout {
+    has_header: bool,
+    headers: Vec<String>,
+    mapping: Option<ColumnMapping>,
+}
+
+struct CsvLayout {
+    delimiter: u8,
+    table: TableLayout,
+}
+
+/// Rows read from a file together with the layout resolved from its first row.
+struct Table<I> {
+    layout: TableLayout,
+    rows: I,
+}
+
+/// One data row with the line it came from, or why it could not be read.
+type TableRow = std::result::Result<(u64, Vec<String>), (u64, String)>;
+
+/// Maps a header cell onto a word field, accepting common English and Chinese names.
+fn header_field(header: &str) -> Option<&'static str> {
+    let normalized = header
+        .trim_start_matches('\u{feff}')
+        .trim()
+        .to_lowercase()
+        .replace([' ', '-'], "_");
+    let field = match normalized.as_str() {
+        "word" | "words" | "term" | "headword" | "单词" | "词汇" | "英文" => "word",
+        "phonetic" | "phonetics" | "pronunciation" | "ipa" | "音标" => "phonetic",
+        "part_of_speech_and_meanings"
+        | "meaning"
+        | "meanings"
+        | "definition"
+        | "definitions"
+        | "translation"
+        | "释义"
+        | "词义"
+        | "中文"
+        | "意思" => "part_of_speech_and_meanings",
+        "example_sentence" | "example" | "examples" | "sentence" | "例句" => "example_sentence",
+        "example_translation" | "sentence_translation" | "例句翻译" | "例句释义" => {
+            "example_translation"
+        }
+        "audio_uk" | "uk_audio" | "英音" => "audio_uk",
+        "audio_us" | "us_audio" | "audio" | "美音" => "audio_us",
+        _ => return None,
+    };
+    Some(field)
+}
+
+fn suggest_mapping(headers: &[String]) -> Option<ColumnMapping> {
+    let find = |field: &str| {
+        headers
+            .iter()
+            .position(|header| header_field(header) == Some(field))
+    };
+    Some(ColumnMapping {
+        word: find("word")?,
+        phonetic: find("phonetic"),
+        part_of_speech_and_meanings: find("part_of_speech_and_meanings"),
+        example_sentence: find("example_sentence"),
+        example_translation: find("example_translation"),
+        audio_uk: find("audio_uk"),
+        audio_us: find("audio_us"),
+    })
+}
+
+/// Header-less files are assumed to start with the word followed by its meaning.
+fn positional_mapping(columns: usize) -> ColumnMapping {
+    ColumnMapping {
+        word: 0,
+        part_of_speech_and_meanings: (columns > 1).then_some(1),
+        ..ColumnMapping::default()
+    }
+}
+
+/// Picks the candidate separator that appears most often outside quotes on the first line.
+fn detect_delimiter(content: &str) -> u8 {
+    let first_line = content.lines().next().unwrap_or_default();
+    let mut counts = [0_usize; DELIMITER_CANDIDATES.len()];
+    let mut quoted = false;
+    for byte in first_line.bytes() {
+        if byte == b'"' {
+            quoted = !quoted;
+        } else if !quoted {
+            if let Some(index) = DELIMITER_CANDIDATES
+                .iter()
+                .position(|candidate| *candidate == byte)
+            {
+                counts[index] += 1;
+            }
+        }
+    }
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .max_by_key(|(index, count)| (**count, std::cmp::Reverse(*index)))
+        .map(|(index, _)| DELIMITER_CANDIDATES[index])
+        .unwrap_or(b',')
+}
+
+fn reader(content: &str, delimiter: u8) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes())
+}
+
+fn resolve_layout(content: &str, options: &CsvImportOptions) -> Result<CsvLayout> {
+    let delimiter = options
+        .delimiter
+        .byte()
+        .unwrap_or_else(|| detect_delimiter(content));
+    let first_row: Vec<String> = match reader(content, delimiter).records().next() {
+        Some(row) => row
+            .context("读取 CSV 表头失败")?
+            .iter()
+            .map(str::to_string)
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(CsvLayout {
+        delimiter,
+        table: resolve_table_layout(first_row, options),
+    })
+}
+
+fn resolve_table_layout(first_row: Vec<String>, options: &CsvImportOptions) -> TableLayout {
+    let first_row: Vec<String> = first_row
+        .iter()
+        .map(|cell| cell.trim_start_matches('\u{feff}').trim().to_string())
+        .collect();
+    let has_header = options
+        .has_header
+        .unwrap_or_else(|| first_row.iter().any(|cell| header_field(cell).is_some()));
+
+    let (headers, detected) = if has_header {
+        let mapping = suggest_mapping(&first_row);
+        (first_row, mapping)
+    } else {
+        let headers = (1..=first_row.len())
+            .map(|index| format!("第 {index} 列"))
+            .collect();
+        (
+            headers,
+            (!first_row.is_empty()).then(|| positional_mapping(first_row.len())),
+        )
+    };
+
+    TableLayout {
+        has_header,
+        headers,
+        mapping: options.mapping.clone().or(detected),
+    }
+}
+
+fn table_row(row: csv::Result<csv::StringRecord>) -> TableRow {
+    match row {
+        Ok(row) => {
+            let line = row.position().map_or(0, |position| position.line());
+            Ok((line, row.iter().map(str::to_string).collect()))
+        }
+        Err(error) => {
+            let line = error.position().map_or(0, |position| position.line());
+            Err((line, format!("解析 CSV 行失败: {error}")))
+        }
+    }
+}
+
+/// Streamed files decode with replacement characters, so a row that does not
+/// fit the encoding detected from the start of the file is reported instead
+/// of being imported as garbage.
+fn decoded_table_row(row: csv::Result<csv::StringRecord>, encoding_name: &str) -> TableRow {
+    let (line, cells) = table_row(row)?;
+    if cells
+        .iter()
+        .any(|cell| cell.contains(char::REPLACEMENT_CHARACTER))
+    {
+        return Err((
+            line,
+            format!("包含无法按 {encoding_name} 解码的字符，请指定编码后重试"),
+        ));
+    }
+    Ok((line, cells))
+}
+
+fn cell(row: &[String], column: Option<usize>) -> Option<String> {
+    column.and_then(|index| row.get(index)).cloned()
+}
+
+fn record_from_row(row: &[String], mapping: &ColumnMapping) -> DictionaryRecord {
+    DictionaryRecord {
+        word: cell(row, Some(mapping.word)).unwrap_or_default(),
+        phonetic: cell(row, mapping.phonetic),
+        part_of_speech_and_meanings: cell(row, mapping.part_of_speech_and_meanings),
+        example_sentence: cell(row, mapping.example_sentence),
+        example_translation: cell(row, mapping.example_translation),
+        audio_uk: cell(row, mapping.audio_uk),
+        audio_us: cell(row, mapping.audio_us),
+    }
+}
+
+pub fn preview_dictionary_csv(csv_content: &str, options: &CsvImportOptions) -> Result<CsvPreview> {
+    let content = csv_content.trim_start_matches('\u{feff}');
+    let CsvLayout { delimiter, table } = resolve_layout(content, options)?;
+    let rows = reader(content, delimiter)
+        .records()
+        .skip(usize::from(table.has_header))
+        .take(PREVIEW_ROW_LIMIT)
+        .map(|row| {
+            row.map(|row| row.iter().map(str::to_string).collect())
+                .context("解析 CSV 行失败")
+        })
+        .collect::<Result<Vec<Vec<String>>>>()?;
+
+    Ok(CsvPreview {
+        delimiter: CsvDelimiter::from_byte(delimiter),
+        has_header: table.has_header,
+        headers: table.headers,
+        rows,
+        mapping: table.mapping,
+        encoding: None,
+    })
+}
+
+/// Recognises BOM-less UTF-16 by the NUL high bytes of its ASCII characters.
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(ENCODING_SNIFF_LIMIT) & !1];
+    let units = sample.len() / 2;
+    if units < 2 {
+        return None;
+    }
+    let even_nuls = sample.iter().step_by(2).filter(|byte| **byte == 0).count();
+    let odd_nuls = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|byte| **byte == 0)
+        .count();
+    if odd_nuls * 2 >= units && even_nuls == 0 {
+        Some(UTF_16LE)
+    } else if even_nuls * 2 >= units && odd_nuls == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// GBK is the two-byte subset of GB18030; only GB18030 has four-byte
+/// sequences, whose second byte is an ASCII digit.
+fn uses_gb18030_extensions(bytes: &[u8]) -> bool {
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] < 0x80 {
+            index += 1;
+        } else if bytes.get(index + 1).is_some_and(u8::is_ascii_digit) {
+            return true;
+        } else {
+            index += 2;
+        }
+    }
+    false
+}
+
+/// Decodes a dictionary file, honouring an explicit encoding label first, then
+/// a byte order mark, then UTF-8, UTF-16 and finally the GBK/GB18030 family
+/// that Excel uses for Chinese spreadsheets.
+pub(crate) fn decode_dictionary_bytes(bytes: &[u8], label: Option<&str>) -> Result<DecodedText> {
+    decode_sample(bytes, label, false)
+}
+
+/// Decodes `bytes`, which are only the start of the file when `truncated`;
+/// a character cut off at the end of such a sample is not an error.
+fn decode_sample(bytes: &[u8], label: Option<&str>, truncated: bool) -> Result<DecodedText> {
+    let forced = match label.map(str::trim).filter(|label| !label.is_empty()) {
+        Some(label) => match Encoding::for_label(label.as_bytes()) {
+            Some(encoding) => Some(encoding),
+            None => bail!("不支持的文件编码: {label}"),
+        },
+        None => None,
+    };
+    let is_utf8 = match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(error) => truncated && error.error_len().is_none(),
+    };
+
+    let (encoding, body) = match (forced, Encoding::for_bom(bytes)) {
+        (Some(encoding), Some((bom, length))) if bom == encoding => (encoding, &bytes[length..]),
+        (Some(encoding), _) => (encoding, bytes),
+        (None, Some((bom, length))) => (bom, &bytes[length..]),
+        (None, None) if is_utf8 => (UTF_8, bytes),
+        (None, None) => (sniff_utf16(bytes).unwrap_or(GB18030), bytes),
+    };
+
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::with_capacity(
+        decoder
+            .max_utf8_buffer_length(body.len())
+            .unwrap_or(body.len()),
+    );
+    let (_, _, had_errors) = decoder.decode_to_string(body, &mut text, !truncated);
+    if had_errors {
+        if forced.is_some() {
+            bail!("文件不是有效的 {} 编码", encoding.name());
+        }
+        bail!("无法识别文件编码，请指定编码后重试");
+    }
+
+    let name = if encoding == GB18030 && !uses_gb18030_extensions(body) {
+        "GBK"
+    } else {
+        encoding.name()
+    };
+    Ok(DecodedText {
+        text,
+        encoding,
+        encoding_name: name,
+    })
+}
+
+pub fn preview_dictionary_csv_bytes(
+    csv_bytes: &[u8],
+    options: &CsvImportOptions,
+) -> Result<CsvPreview> {
+    let decoded = decode_dictionary_bytes(csv_bytes, options.encoding.as_deref())?;
+    let mut preview = preview_dictionary_csv(&decoded.text, options)?;
+    preview.encoding = Some(decoded.encoding_name.to_string());
+    Ok(preview)
+}
+
+/// Stops a strict import at the first bad row; lenient imports record it and move on.
+fn reject_row(
+    summary: &mut ImportSummary,
+    lenient: bool,
+    line: u64,
+    word: Option<String>,
+    reason: String,
+) -> Result<()> {
+    if !lenient {
+        bail!("第 {line} 行: {reason}");
+    }
+    summary.failed += 1;
+    summary.rows.push(ImportRowReport {
+        line,
+        word,
+        status: ImportRowStatus::Failed,
+        reason: Some(reason),
+        changes: Vec::new(),
+    });
+    Ok(())
+}
+
+/// Lets a caller follow a long import and stop it between batches.
+#[derive(Default)]
+pub(crate) struct ImportControl<'a> {
+    pub(crate) cancel: Option<&'a AtomicBool>,
+    /// Called with the number of data rows processed so far.
+    pub(crate) on_progress: Option<&'a (dyn Fn(u64) + Send + Sync)>,
+}
+
+impl ImportControl<'_> {
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    fn report(&self, processed: u64) {
+        if let Some(on_progress) = self.on_progress {
+            on_progress(processed);
+        }
+    }
+}
+
+/// A row waiting to be written with the rest of its batch.
+struct PendingRow {
+    line: u64,
+    record: DictionaryRecord,
+}
+
+async fn flush_pending_rows(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    target: &ImportTarget,
+    pending: &mut Vec<PendingRow>,
+    policy: MergePolicy,
+    summary: &mut ImportSummary,
+) -> Result<()> {
+    let (lines, records): (Vec<u64>, Vec<DictionaryRecord>) =
+        pending.drain(..).map(|row| (row.line, row.record)).unzip();
+    let words: Vec<String> = records.iter().map(|record| record.word.clone()).collect();
+    let upserted = word_bank::upsert_dictionary_records(tx, target, records, policy).await?;
+    for ((line, word), upserted) in lines.into_iter().zip(words).zip(upserted) {
+        let Some(upserted) = upserted else {
+            continue;
+        };
+        summary.upserted += 1;
+        if !upserted.changes.is_empty() {
+            summary.rows.push(ImportRowReport {
+                line,
+                word: Some(word),
+                status: ImportRowStatus::Updated,
+                reason: None,
+                changes: upserted.changes,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Validates the mapping and upserts every data row into the list in batches.
+/// Shared by the CSV, spreadsheet and file importers.
+///
+/// The whole file is written in one transaction on the app's single pooled
+/// connection, so every other database command waits until the import commits,
+/// rolls back or is cancelled. Cancellation is checked before each row.
+async fn import_table_rows(
+    pool: &SqlitePool,
+    name: &str,
+    source: &ImportSource<'_>,
+    table: Table<impl IntoIterator<Item = TableRow>>,
+    overwrite: bool,
+    options: &CsvImportOptions,
+    control: &ImportControl<'_>,
+) -> Result<ImportSummary> {
+    let Table { layout, rows } = table;
+    let Some(mapping) = &layout.mapping else {
+        bail!("文件缺少必需列: word");
+    };
+    if mapping.word >= layout.headers.len() {
+        bail!("单词列超出文件列数");
+    }
+    let required_columns = mapping.last_column() + 1;
+
+    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+    let target = word_bank::prepare_import_list(&mut tx, name, overwrite, source).await?;
+
+    let mut summary = ImportSummary {
+        dry_run: options.dry_run,
+        merge_policy: options.merge_policy,
+        ..ImportSummary::default()
+    };
+    let mut pending = Vec::with_capacity(IMPORT_BATCH_ROWS);
+
+    for row in rows.into_iter().skip(usize::from(layout.has_header)) {
+        if control.is_cancelled() {
+            tx.rollback().await.context("回滚数据库事务失败")?;
+            bail!("导入已取消");
+        }
+        summary.total += 1;
+        let (line, row) = match row {
+            Ok(row) => row,
+            Err((line, reason)) => {
+                reject_row(&mut summary, options.lenient, line, None, reason)?;
+                continue;
+            }
+        };
+        let record = record_from_row(&row, mapping).normalized();
+
+        if row.len() < required_columns {
+            let word = (!record.word.is_empty()).then_some(record.word);
+            reject_row(
+                &mut summary,
+                options.lenient,
+                line,
+                word,
+                format!(
+                    "列数不足：需要 {required_columns} 列，实际 {} 列",
+                    row.len()
+                ),
+            )?;
+            continue;
+        }
+        if record.word.is_empty() {
+            summary.skipped += 1;
+            summary.rows.push(ImportRowReport {
+                line,
+                word: None,
+                status: ImportRowStatus::Skipped,
+                reason: Some("单词为空".to_string()),
+                changes: Vec::new(),
+            });
+            continue;
+        }
+
+        pending.push(PendingRow { line, record });
+        if pending.len() >= IMPORT_BATCH_ROWS {
+            flush_pending_rows(
+                &mut tx,
+                &target,
+                &mut pending,
+                options.merge_policy,
+                &mut summary,
+            )
+            .await?;
+            control.report(summary.total);
+        }
+    }
+    flush_pending_rows(
+        &mut tx,
+        &target,
+        &mut pending,
+        options.merge_policy,
+        &mut summary,
+    )
+    .await?;
+    control.report(summary.total);
+    // Batched updates are reported after the rows rejected meanwhile.
+    summary.rows.sort_by_key(|row| row.line);
+
+    // A dry run goes through the same writes so duplicates within the file are
+    // reported accurately, then discards them.
+    if options.dry_run {
+        tx.rollback().await.context("回滚数据库事务失败")?;
+    } else {
+        tx.commit().await.context("提交数据库事务失败")?;
+    }
+    Ok(summary)
+}
+
+pub(crate) async fn import_dictionary_csv_with_pool(
+    pool: &SqlitePool,
+    name: &str,
+    csv_content: &str,
+    overwrite: bool,
+    options: &CsvImportOptions,
+) -> Result<ImportSummary> {
+    let content = csv_content.trim_start_matches('\u{feff}');
+    let CsvLayout { delimiter, table } = resolve_layout(content, options)?;
+    let rows = reader(content, delimiter).into_records().map(table_row);
+    let source = ImportSource {
+        kind: "csv",
+        name: None,
+        merge_policy: options.merge_policy,
+    };
+    import_table_rows(
+        pool,
+        name,
+        &source,
+        Table {
+            layout: table,
+            rows,
+        },
+        overwrite,
+        options,
+        &ImportControl::default(),
+    )
+    .await
+}
+
+pub async fn import_dictionary_csv(
+    db: &Database,
+    name: &str,
+    csv_content: &str,
+    overwrite: bool,
+    options: &CsvImportOptions,
+) -> Result<ImportSummary> {
+    let pool = db.pool()?;
+    if overwrite && !options.dry_run {
+        backup::create_automatic_backup(db, "overwrite-import").await?;
+    }
+    import_dictionary_csv_with_pool(&pool, name, csv_content, overwrite, options).await
+}
+
+pub(crate) async fn import_dictionary_csv_bytes_with_pool(
+    pool: &SqlitePool,
+    name: &str,
+    csv_bytes: &[u8],
+    overwrite: bool,
+    options: &CsvImportOptions,
+) -> Result<ImportSummary> {
+    let decoded = decode_dictionary_bytes(csv_bytes, options.encoding.as_deref())?;
+    let mut summary =
+        import_dictionary_csv_with_pool(pool, name, &decoded.text, overwrite, options).await?;
+    summary.encoding = Some(decoded.encoding_name.to_string());
+    Ok(summary)
+}
+
+pub async fn import_dictionary_csv_bytes(
+    db: &Database,
+    name: &str,
+    csv_bytes: &[u8],
+    overwrite: bool,
+    options: &CsvImportOptions,
+) -> Result<ImportSummary> {
+    let pool = db.pool()?;
+    if overwrite && !options.dry_run {
+        backup::create_automatic_backup(db, "overwrite-import").await?;
+    }
+    import_dictionary_csv_bytes_with_pool(&pool, name, csv_bytes, overwrite, options).await
+}
+
+/// A worksheet read into text cells, with the row number its first row sits on.
+struct Worksheet {
+    sheets: Vec<String>,
+    name: String,
+    first_line: u64,
+    rows: Vec<Vec<String>>,
+}
+
+fn sheet_cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        cell => cell.to_string(),
+    }
+}
+
+/// Reads an .xlsx, .xls or .ods worksheet: the chosen one, or the first.
+fn read_worksheet(path: &Path, sheet: Option<&str>) -> Result<Worksheet> {
+    let mut workbook = open_workbook_auto(path)
+        .with_context(|| format!("无法打开表格文件: {}", path.display()))?;
+    let sheets = workbook.sheet_names().to_vec();
+    let name = match sheet.map(str::trim).filter(|sheet| !sheet.is_empty()) {
+        Some(sheet) if sheets.iter().any(|name| name == sheet) => sheet.to_string(),
+        Some(sheet) => bail!("工作表不存在: {sheet}"),
+        None => sheets.first().cloned().context("表格文件没有工作表")?,
+    };
+    let range = workbook
+        .worksheet_range(&name)
+        .with_context(|| format!("读取工作表失败: {name}"))?;
+    let first_line = range.start().map_or(1, |(row, _)| u64::from(row) + 1);
+    let rows = range
+        .rows()
+        .map(|row| row.iter().map(sheet_cell_text).collect())
+        .collect();
+    Ok(Worksheet {
+        sheets,
+        name,
+        first_line,
+        rows,
+    })
+}
+
+/// Parses the worksheet on a blocking thread; calamine reads the whole sheet.
+async fn load_worksheet(path: &Path, sheet: Option<&str>) -> Result<Worksheet> {
+    let path = path.to_path_buf();
+    let sheet = sheet.map(str::to_string);
+    run_blocking(move || read_worksheet(&path, sheet.as_deref())).await
+}
+
+fn spreadsheet_layout(worksheet: &Worksheet, options: &CsvImportOptions) -> TableLayout {
+    let first_row = worksheet.rows.first().cloned().unwrap_or_default();
+    resolve_table_layout(first_row, options)
+}
+
+pub fn preview_dictionary_spreadsheet(
+    path: &str,
+    options: &CsvImportOptions,
+) -> Result<SpreadsheetPreview> {
+    let worksheet = read_worksheet(Path::new(path.trim()), options.sheet.as_deref())?;
+    let layout = spreadsheet_layout(&worksheet, options);
+    let rows = worksheet
+        .rows
+        .into_iter()
+        .skip(usize::from(layout.has_header))
+        .take(PREVIEW_ROW_LIMIT)
+        .collect();
+    Ok(SpreadsheetPreview {
+        sheets: worksheet.sheets,
+        sheet: worksheet.name,
+        has_header: layout.has_header,
+        headers: layout.headers,
+        rows,
+        mapping: layout.mapping,
+    })
+}
+
+pub(crate) async fn import_dictionary_spreadsheet_with_pool(
+    pool: &SqlitePool,
+    name: &str,
+    path: &Path,
+    overwrite: bool,
+    options: &CsvImportOptions,
+) -> Result<ImportSummary> {
+    let worksheet = load_worksheet(path, options.sheet.as_deref()).await?;
+    let layout = spreadsheet_layout(&worksheet, options);
+    let first_line = worksheet.first_line;
+    let rows = (first_line..).zip(worksheet.rows).map(Ok);
+    let source = ImportSource {
+        kind: spreadsheet_kind(path).unwrap_or("spreadsheet"),
+        name: path.file_name().and_then(|name| name.to_str()),
+        merge_policy: options.merge_policy,
+    };
+    import_table_rows(
+        pool,
+        name,
+        &source,
+        Table { layout, rows },
+        overwrite,
+        options,
+        &ImportControl::default(),
+    )
+    .await
+}
+
+pub async fn import_dictionary_spreadsheet(
+    db: &Database,
+    name: &str,
+    path: &str,
+    overwrite: bool,
+    options: &CsvImportOptions,
+) -> Result<ImportSummary> {
+    let pool = db.pool()?;
+    if overwrite && !options.dry_run {
+        backup::create_automatic_backup(db, "overwrite-import").await?;
+    }
+    import_dictionary_spreadsheet_with_pool(&pool, name, Path::new(path.trim()), overwrite, options)
+        .await
+}
+
+fn open_decoded(path: &Path, encoding: &'static Encoding) -> Result<Box<dyn Read + Send>> {
+    let file = File::open(path).with_context(|| format!("无法打开词库文件: {}", path.display()))?;
+    Ok(Box::new(
+        DecodeReaderBytesBuilder::new()
+            .encoding(Some(encoding))
+            .strip_bom(true)
+            .build(file),
+    ))
+}
+
+/// Counts CSV records in a pass that skips field decoding, for progress totals.
+fn count_csv_records(path: &Path, encoding: &'static Encoding, delimiter: u8) -> Result<u64> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(open_decoded(path, encoding)?);
+    let mut record = csv::ByteRecord::new();
+    let mut count = 0;
+    loop {
+        match reader.read_byte_record(&mut record) {
+            Ok(true) => count += 1,
+            Ok(false) => break,
+            Err(error) if error.is_io_error() => {
+                return Err(error).with_context(|| format!("读取词库文件失败: {}", path.display()));
+            }
+            // Malformed rows are counted too; the import reports them.
+            Err(_) => count += 1,
+        }
+    }
+    Ok(count)
+}
+
+/// Imports a CSV or spreadsheet file from disk. CSV rows are streamed and
+/// decoded on the fly instead of being loaded into memory first.
+/// `on_progress` receives processed and total data rows after every batch.
+pub(crate) async fn import_dictionary_file_with_pool(
+    pool: &SqlitePool,
+    name: &str,
+    path: &Path,
+    overwrite: bool,
+    options: &CsvImportOptions,
+    cancel: &AtomicBool,
+    on_progress: &(dyn Fn(u64, u64) + Send + Sync),
+) -> Result<ImportSummary> {
+    let source_name = path.file_name().and_then(|name| name.to_str());
+
+    if let Some(kind) = spreadsheet_kind(path) {
+        let worksheet = load_worksheet(path, options.sheet.as_deref()).await?;
+        let layout = spreadsheet_layout(&worksheet, options);
+        let total = (worksheet.rows.len() as u64).saturating_sub(u64::from(layout.has_header));
+        let report = |processed| on_progress(processed, total);
+        let source = ImportSource {
+            kind,
+            name: source_name,
+            merge_policy: options.merge_policy,
+        };
+        let rows = (worksheet.first_line..).zip(worksheet.rows).map(Ok);
+        on_progress(0, total);
+        return import_table_rows(
+            pool,
+            name,
+            &source,
+            Table { layout, rows },
+            overwrite,
+            options,
+            &ImportControl {
+                cancel: Some(cancel),
+                on_progress: Some(&report),
+            },
+        )
+        .await;
+    }
+
+    let mut sample = Vec::with_capacity(FILE_SAMPLE_BYTES);
+    File::open(path)
+        .with_context(|| format!("无法打开词库文件: {}", path.display()))?
+        .take(FILE_SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)
+        .with_context(|| format!("读取词库文件失败: {}", path.display()))?;
+    let decoded = decode_sample(
+        &sample,
+        options.encoding.as_deref(),
+        sample.len() == FILE_SAMPLE_BYTES,
+    )?;
+    let content = decoded.text.trim_start_matches('\u{feff}');
+    let CsvLayout { delimiter, table } = resolve_layout(content, options)?;
+
+    let total = {
+        let path = path.to_path_buf();
+        let encoding = decoded.encoding;
+        run_blocking(move || count_csv_records(&path, encoding, delimiter)).await?
+    }
+    .saturating_sub(u64::from(table.has_header));
+    let report = |processed| on_progress(processed, total);
+    let rows = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(open_decoded(path, decoded.encoding)?)
+        .into_records()
+        .map(|row| decoded_table_row(row, decoded.encoding_name));
+    let source = ImportSource {
+        kind: "csv",
+        name: source_name,
+        merge_policy: options.merge_policy,
+    };
+    on_progress(0, total);
+    let mut summary = import_table_rows(
+        pool,
+        name,
+        &source,
+        Table {
+            layout: table,
+            rows,
+        },
+        overwrite,
+        options,
+        &ImportControl {
+            cancel: Some(cancel),
+            on_progress: Some(&report),
+        },
+    )
+    .await?;
+    summary.encoding = Some(decoded.encoding_name.to_string());
+    Ok(summary)
+}
+
+/// Imports a dictionary file, emitting [`IMPORT_PROGRESS_EVENT`] as batches are
+/// written. The import can be stopped with `token` until it finishes; other
+/// database commands wait for it, since it holds the only pooled connection.
+pub async fn import_dictionary_file(
+    app: &tauri::AppHandle,
+    token: &str,
+    name: &str,
+    path: &str,
+    overwrite: bool,
+    options: &CsvImportOptions,
+) -> Result<ImportSummary> {
+    let db = app.state::<Database>().inner();
+    let cancellation = app.state::<ImportCancellation>().inner();
+    let pool = db.pool()?;
+    if overwrite && !options.dry_run {
+        backup::create_automatic_backup(db, "overwrite-import").await?;
+    }
+
+    let cancel = cancellation.register(token);
+    let on_progress = |processed, total| {
+        let _ = app.emit(
+            IMPORT_PROGRESS_EVENT,
+            ImportProgress {
+                token: token.to_string(),
+                processed,
+                total,
+            },
+        );
+    };
+    let result = import_dictionary_file_with_pool(
+        &pool,
+        name,
+        Path::new(path.trim()),
+        overwrite,
+        options,
+        &cancel,
+        &on_progress,
+    )
+    .await;
+    cancellation.finish(token);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word_bank::FieldChange;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use tauri::async_runtime;
+
+    async fn setup_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(SqliteConnectOptions::new().filename(":memory:"))
+            .await
+            .expect("Failed to open test database");
+        word_bank::ensure_schema(&pool)
+            .await
+            .expect("Failed to ensure schema");
+        pool
+    }
+
+    async fn meanings_of(pool: &SqlitePool, word: &str) -> Option<String> {
+        sqlx::query_scalar("SELECT part_of_speech_and_meanings FROM word WHERE word = ?")
+            .bind(word)
+            .fetch_one(pool)
+            .await
+            .expect("Failed to load word")
+    }
+
+    #[test]
+    fn preview_detects_separator_header_and_mapping() {
+        let preview = preview_dictionary_csv(
+            "Word\tPhonetic\tMeaning\napple\t/ˈæp.əl/\tn. 苹果\n",
+            &CsvImportOptions::default(),
+        )
+        .expect("Failed to preview");
+        assert_eq!(preview.delimiter, CsvDelimiter::Tab);
+        assert!(preview.has_header);
+        assert_eq!(preview.headers, ["Word", "Phonetic", "Meaning"]);
+        assert_eq!(preview.rows, [["apple", "/ˈæp.əl/", "n. 苹果"]]);
+        let mapping = preview.mapping.expect("mapping");
+        assert_eq!(mapping.word, 0);
+        assert_eq!(mapping.phonetic, Some(1));
+        assert_eq!(mapping.part_of_speech_and_meanings, Some(2));
+        assert_eq!(mapping.audio_uk, None);
+
+        let headerless = preview_dictionary_csv(
+            "apple;\"n. 苹果; 苹果树\"\nbanana;n. 香蕉\n",
+            &CsvImportOptions::default(),
+        )
+        .expect("Failed to preview");
+        assert_eq!(headerless.delimiter, CsvDelimiter::Semicolon);
+        assert!(!headerless.has_header);
+        assert_eq!(headerless.rows.len(), 2);
+        assert_eq!(headerless.rows[0][1], "n. 苹果; 苹果树");
+        assert_eq!(headerless.mapping, Some(positional_mapping(2)));
+    }
+
+    #[test]
+    fn byte_imports_detect_legacy_encodings() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let csv = "word,释义\napple,n. 苹果\n";
+            let (gbk, _, _) = encoding_rs::GBK.encode(csv);
+            let utf16le: Vec<u8> = [0xff, 0xfe]
+                .into_iter()
+                .chain(csv.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect();
+            let utf16be: Vec<u8> = csv.encode_utf16().flat_map(u16::to_be_bytes).collect();
+            let (gb18030, _, _) = GB18030.encode("word,释义\n𠀀,n. 罕用字\n");
+
+            for (bytes, encoding) in [
+                (gbk.as_ref(), "GBK"),
+                (utf16le.as_slice(), "UTF-16LE"),
+                (utf16be.as_slice(), "UTF-16BE"),
+                (csv.as_bytes(), "UTF-8"),
+            ] {
+                let summary = import_dictionary_csv_bytes_with_pool(
+                    &pool,
+                    "cet4",
+                    bytes,
+                    true,
+                    &CsvImportOptions::default(),
+                )
+                .await
+                .expect("Failed to import");
+                assert_eq!(summary.encoding.as_deref(), Some(encoding));
+                assert_eq!(summary.upserted, 1);
+                assert_eq!(
+                    meanings_of(&pool, "apple").await.as_deref(),
+                    Some("n. 苹果")
+                );
+            }
+
+            let preview = preview_dictionary_csv_bytes(&gb18030, &CsvImportOptions::default())
+                .expect("Failed to preview");
+            assert_eq!(preview.encoding.as_deref(), Some("gb18030"));
+            assert_eq!(preview.rows, [["𠀀", "n. 罕用字"]]);
+
+            let forced = CsvImportOptions {
+                encoding: Some("utf-8".to_string()),
+                ..CsvImportOptions::default()
+            };
+            assert!(preview_dictionary_csv_bytes(&gbk, &forced).is_err());
+            let unknown = CsvImportOptions {
+                encoding: Some("klingon".to_string()),
+                ..CsvImportOptions::default()
+            };
+            assert!(preview_dictionary_csv_bytes(csv.as_bytes(), &unknown).is_err());
+        });
+    }
+
+    fn sheet_xml(rows: &[(u32, &[&str])]) -> String {
+        let rows: String = rows
+            .iter()
+            .map(|(line, cells)| {
+                let cells: String = cells
+                    .iter()
+                    .zip('A'..)
+                    .map(|(value, column)| match value.parse::<f64>() {
+                        Ok(number) => format!("<c r=\"{column}{line}\"><v>{number}</v></c>"),
+                        Err(_) => format!(
+                            "<c r=\"{column}{line}\" t=\"inlineStr\"><is><t>{value}</t></is></c>"
+                        ),
+                    })
+                    .collect();
+                format!("<row r=\"{line}\">{cells}</row>")
+            })
+            .collect();
+        format!(
+            "<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>{rows}</sheetData></worksheet>"
+        )
+    }
+
+    fn write_workbook(path: &Path, sheets: &[(&str, String)]) {
+        use std::io::Write;
+
+        let main = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+        let relationships = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+        let package = "http://schemas.openxmlformats.org/package/2006/relationships";
+        let mut overrides = String::new();
+        let mut entries = String::new();
+        let mut links = String::new();
+        for (index, (name, _)) in sheets.iter().enumerate() {
+            let id = index + 1;
+            overrides.push_str(&format!(
+                "<Override PartName=\"/xl/worksheets/sheet{id}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+            ));
+            entries.push_str(&format!(
+                "<sheet name=\"{name}\" sheetId=\"{id}\" r:id=\"rId{id}\"/>"
+            ));
+            links.push_str(&format!(
+                "<Relationship Id=\"rId{id}\" Type=\"{relationships}/worksheet\" Target=\"worksheets/sheet{id}.xml\"/>"
+            ));
+        }
+        let mut parts = vec![
+            (
+                "[Content_Types].xml".to_string(),
+                format!(
+                    "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\"><Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/><Default Extension=\"xml\" ContentType=\"application/xml\"/><Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>{overrides}</Types>"
+                ),
+            ),
+            (
+                "_rels/.rels".to_string(),
+                format!(
+                    "<Relationships xmlns=\"{package}\"><Relationship Id=\"rId1\" Type=\"{relationships}/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>"
+                ),
+            ),
+            (
+                "xl/workbook.xml".to_string(),
+                format!(
+                    "<workbook xmlns=\"{main}\" xmlns:r=\"{relationships}\"><sheets>{entries}</sheets></workbook>"
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels".to_string(),
+                format!("<Relationships xmlns=\"{package}\">{links}</Relationships>"),
+            ),
+        ];
+        for (index, (_, xml)) in sheets.iter().enumerate() {
+            parts.push((format!("xl/worksheets/sheet{}.xml", index + 1), xml.clone()));
+        }
+
+        let file = std::fs::File::create(path).expect("Failed to create workbook");
+        let mut zip = zip::ZipWriter::new(file);
+        for (name, content) in parts {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .expect("Failed to add workbook part");
+            zip.write_all(content.as_bytes())
+                .expect("Failed to write workbook part");
+        }
+        zip.finish().expect("Failed to finish workbook");
+    }
+
+    #[test]
+    fn spreadsheet_imports_read_the_chosen_sheet() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let path =
+                std::env::temp_dir().join(format!("word-card-sheet-{}.xlsx", std::process::id()));
+            write_workbook(
+                &path,
+                &[
+                    ("说明", sheet_xml(&[(1, &["本表仅供参考"])])),
+                    (
+                        "Words",
+                        sheet_xml(&[
+                            (2, &["单词", "音标", "释义"]),
+                            (3, &["apple", "/ˈæp.əl/", "n. 苹果"]),
+                            (4, &["", "", "n. 空行"]),
+                            (5, &["2024", "", "num. 年份"]),
+                        ]),
+                    ),
+                ],
+            );
+            let path_text = path.to_string_lossy().into_owned();
+
+            let preview = preview_dictionary_spreadsheet(&path_text, &CsvImportOptions::default())
+                .expect("Failed to preview");
+            assert_eq!(preview.sheets, ["说明", "Words"]);
+            assert_eq!(preview.sheet, "说明");
+
+            let options = CsvImportOptions {
+                sheet: Some("Words".to_string()),
+                ..CsvImportOptions::default()
+            };
+            let preview =
+                preview_dictionary_spreadsheet(&path_text, &options).expect("Failed to preview");
+            assert!(preview.has_header);
+            let mapping = preview.mapping.expect("mapping");
+            assert_eq!(mapping.phonetic, Some(1));
+            assert_eq!(mapping.part_of_speech_and_meanings, Some(2));
+
+            let summary =
+                import_dictionary_spreadsheet_with_pool(&pool, "cet4", &path, false, &options)
+                    .await
+                    .expect("Failed to import");
+            assert_eq!(summary.total, 3);
+            assert_eq!(summary.upserted, 2);
+            assert_eq!(summary.rows[0].line, 4);
+            assert_eq!(summary.rows[0].status, ImportRowStatus::Skipped);
+            assert_eq!(
+                meanings_of(&pool, "apple").await.as_deref(),
+                Some("n. 苹果")
+            );
+            assert_eq!(
+                meanings_of(&pool, "2024").await.as_deref(),
+                Some("num. 年份")
+            );
+
+            let missing = CsvImportOptions {
+                sheet: Some("Missing".to_string()),
+                ..CsvImportOptions::default()
+            };
+            assert!(preview_dictionary_spreadsheet(&path_text, &missing).is_err());
+            assert_eq!(spreadsheet_kind(Path::new("list.XLSM")), Some("xlsm"));
+            assert_eq!(spreadsheet_kind(Path::new("list.csv")), None);
+            let _ = std::fs::remove_file(&path);
+        });
+    }
+
+    #[test]
+    fn file_imports_stream_batches_report_progress_and_cancel() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let path =
+                std::env::temp_dir().join(format!("word-card-stream-{}.csv", std::process::id()));
+            let mut csv = String::from("单词\t释义\n");
+            for index in 0..1200 {
+                csv.push_str(&format!("word{index}\t释义{index}\n"));
+            }
+            csv.push_str("word7\t重复\n");
+            let (gbk, _, _) = encoding_rs::GBK.encode(&csv);
+            std::fs::write(&path, &gbk).expect("Failed to write file");
+
+            let cancel = AtomicBool::new(true);
+            let result = import_dictionary_file_with_pool(
+                &pool,
+                "big",
+                &path,
+                false,
+                &CsvImportOptions::default(),
+                &cancel,
+                &|_, _| {},
+            )
+            .await;
+            assert!(result.is_err());
+            let words: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM word")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count words");
+            assert_eq!(words, 0);
+
+            let progress = Mutex::new(Vec::new());
+            let summary = import_dictionary_file_with_pool(
+                &pool,
+                "big",
+                &path,
+                false,
+                &CsvImportOptions::default(),
+                &AtomicBool::new(false),
+                &|processed, total| progress.lock().unwrap().push((processed, total)),
+            )
+            .await
+            .expect("Failed to import");
+            assert_eq!(summary.encoding.as_deref(), Some("GBK"));
+            assert_eq!(summary.total, 1201);
+            assert_eq!(summary.upserted, 1201);
+            assert_eq!(summary.rows.len(), 1);
+            assert_eq!(summary.rows[0].line, 1202);
+            assert_eq!(
+                progress.into_inner().unwrap(),
+                [(0, 1201), (500, 1201), (1000, 1201), (1201, 1201)]
+            );
+            assert_eq!(meanings_of(&pool, "word7").await.as_deref(), Some("重复"));
+            assert_eq!(
+                meanings_of(&pool, "word1199").await.as_deref(),
+                Some("释义1199")
+            );
+            let _ = std::fs::remove_file(&path);
+        });
+    }
+
+    #[test]
+    fn rows_past_the_sample_that_do_not_decode_are_reported() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let path =
+                std::env::temp_dir().join(format!("word-card-late-gbk-{}.csv", std::process::id()));
+            let mut bytes = b"word,meaning\n".to_vec();
+            let mut index: u64 = 0;
+            while bytes.len() <= FILE_SAMPLE_BYTES {
+                bytes.extend_from_slice(format!("word{index},meaning{index}\n").as_bytes());
+                index += 1;
+            }
+            let (gbk, _, _) = encoding_rs::GBK.encode("apple,苹果\n");
+            bytes.extend_from_slice(&gbk);
+            std::fs::write(&path, &bytes).expect("Failed to write file");
+
+            let summary = import_dictionary_file_with_pool(
+                &pool,
+                "late",
+                &path,
+                false,
+                &CsvImportOptions {
+                    lenient: true,
+                    ..CsvImportOptions::default()
+                },
+                &AtomicBool::new(false),
+                &|_, _| {},
+            )
+            .await
+            .expect("Failed to import");
+            assert_eq!(summary.encoding.as_deref(), Some("UTF-8"));
+            assert_eq!(summary.upserted, index);
+            assert_eq!(summary.failed, 1);
+            assert_eq!(summary.rows[0].line, index + 2);
+            let apples: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM word WHERE word = 'apple'")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count words");
+            assert_eq!(apples, 0);
+            let _ = std::fs::remove_file(&path);
+        });
+    }
+
+    #[test]
+    fn imports_with_explicit_mapping_and_rejects_missing_word_column() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let options = CsvImportOptions {
+                mapping: Some(ColumnMapping {
+                    word: 1,
+                    part_of_speech_and_meanings: Some(0),
+                    ..ColumnMapping::default()
+                }),
+                ..CsvImportOptions::default()
+            };
+            let summary = import_dictionary_csv_with_pool(
+                &pool,
+                "mapped",
+                "释义,单词\nn. 苹果,apple\n,\n",
+                false,
+                &options,
+            )
+            .await
+            .expect("Failed to import");
+            assert_eq!(summary.total, 2);
+            assert_eq!(summary.upserted, 1);
+            assert_eq!(summary.skipped, 1);
+            assert_eq!(
+                meanings_of(&pool, "apple").await.as_deref(),
+                Some("n. 苹果")
+            );
+
+            let error = import_dictionary_csv_with_pool(
+                &pool,
+                "broken",
+                "Meaning,Example\nn. 苹果,An apple.\n",
+                false,
+                &CsvImportOptions {
+                    has_header: Some(true),
+                    ..CsvImportOptions::default()
+                },
+            )
+            .await
+            .expect_err("Import without a word column should fail");
+            assert!(error.to_string().contains("word"));
+        });
+    }
+
+    #[test]
+    fn dry_run_reports_changes_without_writing_and_lenient_skips_bad_rows() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            import_dictionary_csv_with_pool(
+                &pool,
+                "base",
+                "word,phonetic,meaning\napple,/ˈæp.əl/,n. 苹果\n",
+                false,
+                &CsvImportOptions::default(),
+            )
+            .await
+            .expect("Failed to import base list");
+
+            let content =
+                "word,phonetic,meaning\napple,/ˈæp.əl/,n. 苹果树\n,,\nshort\nbanana,,n. 香蕉\n";
+            let error = import_dictionary_csv_with_pool(
+                &pool,
+                "strict",
+                content,
+                false,
+                &CsvImportOptions::default(),
+            )
+            .await
+            .expect_err("Strict import should stop at the short row");
+            assert!(error.to_string().starts_with("第 4 行"));
+
+            let report = import_dictionary_csv_with_pool(
+                &pool,
+                "preview",
+                content,
+                false,
+                &CsvImportOptions {
+                    dry_run: true,
+                    lenient: true,
+                    ..CsvImportOptions::default()
+                },
+            )
+            .await
+            .expect("Failed to dry run");
+            assert!(report.dry_run);
+            assert_eq!(report.total, 4);
+            assert_eq!(report.upserted, 2);
+            assert_eq!(report.skipped, 1);
+            assert_eq!(report.failed, 1);
+            let statuses: Vec<(u64, ImportRowStatus)> = report
+                .rows
+                .iter()
+                .map(|row| (row.line, row.status))
+                .collect();
+            assert_eq!(
+                statuses,
+                [
+                    (2, ImportRowStatus::Updated),
+                    (3, ImportRowStatus::Skipped),
+                    (4, ImportRowStatus::Failed),
+                ]
+            );
+            assert_eq!(
+                report.rows[0].changes,
+                [FieldChange {
+                    field: "part_of_speech_and_meanings",
+                    old: Some("n. 苹果".to_string()),
+                    new: Some("n. 苹果树".to_string()),
+                }]
+            );
+
+            assert_eq!(
+                meanings_of(&pool, "apple").await.as_deref(),
+                Some("n. 苹果")
+            );
+            let lists: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM word_list")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count lists");
+            assert_eq!(lists, 1);
+
+            let applied = import_dictionary_csv_with_pool(
+                &pool,
+                "lenient",
+                content,
+                false,
+                &CsvImportOptions {
+                    lenient: true,
+                    ..CsvImportOptions::default()
+                },
+            )
+            .await
+            .expect("Failed to import leniently");
+            assert_eq!(applied.failed, 1);
+            assert_eq!(
+                meanings_of(&pool, "banana").await.as_deref(),
+                Some("n. 香蕉")
+            );
+        });
+    }
+
+    #[test]
+    fn merge_policies_protect_existing_entries() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let rich = "word,phonetic,meaning,example\napple,/ˈæp.əl/,n. 苹果,An apple a day.\n";
+            let thin = "word,phonetic,meaning,example\napple,,n. 苹果树,\npear,,n. 梨,\n";
+            let load = |pool: SqlitePool| async move {
+                sqlx::query_as::<_, (Option<String>, Option<String>, Option<String>)>(
+                    "SELECT phonetic, part_of_speech_and_meanings, example_sentence FROM word WHERE word = 'apple'",
+                )
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to load apple")
+            };
+
+            let cases = [
+                (
+                    MergePolicy::KeepExisting,
+                    (Some("/ˈæp.əl/"), Some("n. 苹果"), Some("An apple a day.")),
+                ),
+                (
+                    MergePolicy::FillEmpty,
+                    (Some("/ˈæp.əl/"), Some("n. 苹果"), Some("An apple a day.")),
+                ),
+                (
+                    MergePolicy::AppendMeanings,
+                    (
+                        Some("/ˈæp.əl/"),
+                        Some("n. 苹果\nn. 苹果树"),
+                        Some("An apple a day."),
+                    ),
+                ),
+                (MergePolicy::Overwrite, (None, Some("n. 苹果树"), None)),
+            ];
+            for (policy, (phonetic, meanings, example)) in cases {
+                import_dictionary_csv_with_pool(
+                    &pool,
+                    "rich",
+                    rich,
+                    false,
+                    &CsvImportOptions::default(),
+                )
+                .await
+                .expect("Failed to import rich list");
+                let summary = import_dictionary_csv_with_pool(
+                    &pool,
+                    "thin",
+                    thin,
+                    false,
+                    &CsvImportOptions {
+                        merge_policy: policy,
+                        ..CsvImportOptions::default()
+                    },
+                )
+                .await
+                .expect("Failed to import thin list");
+                assert_eq!(summary.merge_policy, policy);
+                assert_eq!(summary.upserted, 2);
+                let (stored_phonetic, stored_meanings, stored_example) = load(pool.clone()).await;
+                assert_eq!(stored_phonetic.as_deref(), phonetic, "{policy:?}");
+                assert_eq!(stored_meanings.as_deref(), meanings, "{policy:?}");
+                assert_eq!(stored_example.as_deref(), example, "{policy:?}");
+                assert_eq!(
+                    summary.rows.is_empty(),
+                    policy == MergePolicy::KeepExisting || policy == MergePolicy::FillEmpty,
+                    "{policy:?}"
+                );
+            }
+            assert_eq!(meanings_of(&pool, "pear").await.as_deref(), Some("n. 梨"));
+        });
+    }
+}
@@ -1,3 +1,11 @@
+mod analytics;
+mod anki;
+mod backup;
+mod dictionary_catalog;
+mod dictionary_import;
+mod migrations;
+mod scheduler;
+mod study_stats;
 mod word_bank;
 
 use tauri::{
@@ -42,109 +50,563 @@ fn minimize_window_to_tray(app: &tauri::AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn list_word_lists(app: tauri::AppHandle) -> Result<Vec<word_bank::WordListCard>, String> {
-    word_bank::list_word_lists(&app)
+async fn list_word_lists(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<Vec<word_bank::WordListCard>, String> {
+    word_bank::list_word_lists(db.inner())
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
-async fn create_word_list(app: tauri::AppHandle, name: String) -> Result<i64, String> {
-    word_bank::create_word_list(&app, &name)
+async fn create_word_list(
+    db: tauri::State<'_, word_bank::Database>,
+    name: String,
+) -> Result<i64, String> {
+    word_bank::create_word_list(db.inner(), &name)
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
 async fn import_dictionary_csv(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, word_bank::Database>,
     name: String,
     csv_content: String,
     overwrite: bool,
+    options: Option<dictionary_import::CsvImportOptions>,
+) -> Result<word_bank::ImportSummary, String> {
+    dictionary_import::import_dictionary_csv(
+        db.inner(),
+        &name,
+        &csv_content,
+        overwrite,
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+fn preview_dictionary_csv(
+    csv_content: String,
+    options: Option<dictionary_import::CsvImportOptions>,
+) -> Result<dictionary_import::CsvPreview, String> {
+    dictionary_import::preview_dictionary_csv(&csv_content, &options.unwrap_or_default())
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn import_dictionary_csv_bytes(
+    db: tauri::State<'_, word_bank::Database>,
+    name: String,
+    csv_bytes: Vec<u8>,
+    overwrite: bool,
+    options: Option<dictionary_import::CsvImportOptions>,
 ) -> Result<word_bank::ImportSummary, String> {
-    word_bank::import_dictionary_csv(&app, &name, &csv_content, overwrite)
+    dictionary_import::import_dictionary_csv_bytes(
+        db.inner(),
+        &name,
+        &csv_bytes,
+        overwrite,
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+fn preview_dictionary_csv_bytes(
+    csv_bytes: Vec<u8>,
+    options: Option<dictionary_import::CsvImportOptions>,
+) -> Result<dictionary_import::CsvPreview, String> {
+    dictionary_import::preview_dictionary_csv_bytes(&csv_bytes, &options.unwrap_or_default())
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn import_dictionary_spreadsheet(
+    db: tauri::State<'_, word_bank::Database>,
+    name: String,
+    path: String,
+    overwrite: bool,
+    options: Option<dictionary_import::CsvImportOptions>,
+) -> Result<word_bank::ImportSummary, String> {
+    dictionary_import::import_dictionary_spreadsheet(
+        db.inner(),
+        &name,
+        &path,
+        overwrite,
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+fn preview_dictionary_spreadsheet(
+    path: String,
+    options: Option<dictionary_import::CsvImportOptions>,
+) -> Result<dictionary_import::SpreadsheetPreview, String> {
+    dictionary_import::preview_dictionary_spreadsheet(&path, &options.unwrap_or_default())
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn import_dictionary_file(
+    app: tauri::AppHandle,
+    token: String,
+    name: String,
+    path: String,
+    overwrite: bool,
+    options: Option<dictionary_import::CsvImportOptions>,
+) -> Result<word_bank::ImportSummary, String> {
+    dictionary_import::import_dictionary_file(
+        &app,
+        &token,
+        &name,
+        &path,
+        overwrite,
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+fn cancel_dictionary_import(
+    cancellation: tauri::State<'_, dictionary_import::ImportCancellation>,
+    token: String,
+) -> bool {
+    cancellation.inner().cancel(&token)
+}
+
+#[tauri::command]
+fn get_dictionary_catalog(
+    app: tauri::AppHandle,
+) -> Result<dictionary_catalog::CatalogManifest, String> {
+    dictionary_catalog::get_dictionary_catalog(&app).map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+fn set_dictionary_mirror(
+    app: tauri::AppHandle,
+    mirror: Option<String>,
+) -> Result<dictionary_catalog::CatalogManifest, String> {
+    dictionary_catalog::set_dictionary_mirror(&app, mirror.as_deref())
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn download_dictionary(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<dictionary_catalog::DownloadedDictionary, String> {
+    dictionary_catalog::download_dictionary(&app, &id)
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
-async fn set_active_word_list(app: tauri::AppHandle, word_list_id: i64) -> Result<(), String> {
-    word_bank::set_active_word_list(&app, word_list_id)
+async fn export_word_list_csv(
+    db: tauri::State<'_, word_bank::Database>,
+    word_list_id: i64,
+    include_progress: Option<bool>,
+) -> Result<String, String> {
+    word_bank::export_word_list_csv(db.inner(), word_list_id, include_progress.unwrap_or(false))
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
-async fn clear_active_word_list(app: tauri::AppHandle) -> Result<(), String> {
-    word_bank::clear_active_word_list(&app)
+async fn list_word_definitions(
+    db: tauri::State<'_, word_bank::Database>,
+    word_id: i64,
+) -> Result<Vec<word_bank::WordDefinition>, String> {
+    word_bank::list_word_definitions(db.inner(), word_id)
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
-async fn delete_word_list(app: tauri::AppHandle, word_list_id: i64) -> Result<(), String> {
-    word_bank::delete_word_list(&app, word_list_id)
+async fn inspect_anki_package(path: String) -> Result<Vec<anki::AnkiNoteType>, String> {
+    anki::inspect_anki_package(&path)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn import_anki_package(
+    db: tauri::State<'_, word_bank::Database>,
+    name: String,
+    path: String,
+    mapping: anki::AnkiFieldMapping,
+    options: Option<anki::AnkiImportOptions>,
+    overwrite: bool,
+) -> Result<word_bank::ImportSummary, String> {
+    anki::import_anki_package(
+        db.inner(),
+        &name,
+        &path,
+        &mapping,
+        &options.unwrap_or_default(),
+        overwrite,
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn export_anki_package(
+    db: tauri::State<'_, word_bank::Database>,
+    word_list_id: i64,
+    path: String,
+) -> Result<anki::AnkiExportSummary, String> {
+    anki::export_anki_package(db.inner(), word_list_id, &path)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn set_active_word_list(
+    db: tauri::State<'_, word_bank::Database>,
+    word_list_id: i64,
+) -> Result<(), String> {
+    word_bank::set_active_word_list(db.inner(), word_list_id)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn clear_active_word_list(db: tauri::State<'_, word_bank::Database>) -> Result<(), String> {
+    word_bank::clear_active_word_list(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn delete_word_list(
+    db: tauri::State<'_, word_bank::Database>,
+    word_list_id: i64,
+) -> Result<(), String> {
+    word_bank::delete_word_list(db.inner(), word_list_id)
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
 async fn allocate_learning_session(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, word_bank::Database>,
 ) -> Result<Vec<word_bank::LearningWord>, String> {
-    word_bank::allocate_learning_session(&app)
+    word_bank::allocate_learning_session(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn create_learning_session(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<word_bank::LearningSession, String> {
+    word_bank::create_learning_session(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_learning_session(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<Option<word_bank::LearningSession>, String> {
+    word_bank::get_learning_session(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn advance_learning_session(
+    db: tauri::State<'_, word_bank::Database>,
+    cache: tauri::State<'_, word_bank::StudyCalendarCache>,
+    session_id: i64,
+    grade: Option<scheduler::ReviewGrade>,
+    context: Option<word_bank::StudyEventContext>,
+) -> Result<word_bank::LearningSessionAdvance, String> {
+    word_bank::advance_learning_session(
+        db.inner(),
+        cache.inner(),
+        session_id,
+        grade,
+        context.unwrap_or_default(),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn finish_learning_session(
+    db: tauri::State<'_, word_bank::Database>,
+    session_id: i64,
+) -> Result<(), String> {
+    word_bank::finish_learning_session(db.inner(), session_id)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_study_stats(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<study_stats::StudyStats, String> {
+    study_stats::get_study_stats(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn list_study_log(
+    db: tauri::State<'_, word_bank::Database>,
+    query: Option<study_stats::StudyLogQuery>,
+) -> Result<study_stats::StudyLogPage, String> {
+    study_stats::list_study_log(db.inner(), query.unwrap_or_default())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn summarize_study_log(
+    db: tauri::State<'_, word_bank::Database>,
+    query: Option<study_stats::StudyLogQuery>,
+) -> Result<study_stats::StudyLogSummary, String> {
+    study_stats::summarize_study_log(db.inner(), query.unwrap_or_default())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_retention_by_interval(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<Vec<analytics::RetentionBucket>, String> {
+    analytics::get_retention_by_interval(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_mastery_history(
+    db: tauri::State<'_, word_bank::Database>,
+    days: Option<i64>,
+) -> Result<Vec<analytics::MasterySeries>, String> {
+    analytics::get_mastery_history(db.inner(), days)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn list_hardest_words(
+    db: tauri::State<'_, word_bank::Database>,
+    limit: Option<i64>,
+) -> Result<Vec<analytics::HardWord>, String> {
+    analytics::list_hardest_words(db.inner(), limit)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_answer_latency(
+    db: tauri::State<'_, word_bank::Database>,
+    days: Option<i64>,
+) -> Result<analytics::LatencySeries, String> {
+    analytics::get_answer_latency(db.inner(), days)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn forecast_reviews(
+    db: tauri::State<'_, word_bank::Database>,
+    days: Option<i64>,
+) -> Result<Vec<analytics::ListForecast>, String> {
+    analytics::forecast_reviews(db.inner(), days)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_study_goal(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<study_stats::StudyGoal, String> {
+    study_stats::get_study_goal(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn update_study_goal(
+    db: tauri::State<'_, word_bank::Database>,
+    goal: study_stats::StudyGoal,
+) -> Result<study_stats::StudyGoal, String> {
+    study_stats::update_study_goal(db.inner(), goal)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn add_streak_freeze(
+    db: tauri::State<'_, word_bank::Database>,
+    day: String,
+) -> Result<Vec<String>, String> {
+    study_stats::add_streak_freeze(db.inner(), day)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn remove_streak_freeze(
+    db: tauri::State<'_, word_bank::Database>,
+    day: String,
+) -> Result<Vec<String>, String> {
+    study_stats::remove_streak_freeze(db.inner(), day)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_session_profile(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<word_bank::SessionProfile, String> {
+    word_bank::get_session_profile(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn get_study_clock(
+    db: tauri::State<'_, word_bank::Database>,
+) -> Result<word_bank::StudyClock, String> {
+    word_bank::get_study_clock(db.inner())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn update_study_clock(
+    db: tauri::State<'_, word_bank::Database>,
+    cache: tauri::State<'_, word_bank::StudyCalendarCache>,
+    clock: word_bank::StudyClock,
+) -> Result<word_bank::StudyClock, String> {
+    word_bank::update_study_clock(db.inner(), cache.inner(), clock)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn update_session_profile(
+    db: tauri::State<'_, word_bank::Database>,
+    profile: word_bank::SessionProfile,
+) -> Result<word_bank::SessionProfile, String> {
+    word_bank::update_session_profile(db.inner(), profile)
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
 async fn increment_proficiency(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, word_bank::Database>,
     cache: tauri::State<'_, word_bank::StudyCalendarCache>,
     word_id: i64,
+    context: Option<word_bank::StudyEventContext>,
 ) -> Result<word_bank::LearningProgress, String> {
-    word_bank::increment_proficiency(&app, cache.inner(), word_id)
+    word_bank::increment_proficiency(db.inner(), cache.inner(), word_id, context.unwrap_or_default())
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
 async fn decrement_proficiency(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, word_bank::Database>,
     cache: tauri::State<'_, word_bank::StudyCalendarCache>,
     word_id: i64,
+    context: Option<word_bank::StudyEventContext>,
 ) -> Result<word_bank::LearningProgress, String> {
-    word_bank::decrement_proficiency(&app, cache.inner(), word_id)
+    word_bank::decrement_proficiency(db.inner(), cache.inner(), word_id, context.unwrap_or_default())
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn grade_word(
+    db: tauri::State<'_, word_bank::Database>,
+    cache: tauri::State<'_, word_bank::StudyCalendarCache>,
+    word_id: i64,
+    grade: scheduler::ReviewGrade,
+    context: Option<word_bank::StudyEventContext>,
+) -> Result<word_bank::LearningProgress, String> {
+    word_bank::grade_word(
+        db.inner(),
+        cache.inner(),
+        word_id,
+        grade,
+        context.unwrap_or_default(),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn undo_last_grade(
+    db: tauri::State<'_, word_bank::Database>,
+    cache: tauri::State<'_, word_bank::StudyCalendarCache>,
+    count: Option<i64>,
+) -> Result<word_bank::UndoSummary, String> {
+    word_bank::undo_last_grade(db.inner(), cache.inner(), count)
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
 async fn list_daily_study_counts(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, word_bank::Database>,
     cache: tauri::State<'_, word_bank::StudyCalendarCache>,
 ) -> Result<Vec<word_bank::DailyStudyCount>, String> {
-    word_bank::list_daily_study_counts(&app, cache.inner())
+    word_bank::list_daily_study_counts(db.inner(), cache.inner())
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
 async fn list_fuzzy_words(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, word_bank::Database>,
     sort: Option<String>,
 ) -> Result<Vec<word_bank::FuzzyWordItem>, String> {
-    word_bank::list_fuzzy_words(&app, sort)
+    word_bank::list_fuzzy_words(db.inner(), sort)
         .await
         .map_err(|error| error.to_string())
 }
 
 #[tauri::command]
 async fn clear_fuzzy_marks(
-    app: tauri::AppHandle,
+    db: tauri::State<'_, word_bank::Database>,
     word_ids: Vec<i64>,
 ) -> Result<(), String> {
-    word_bank::clear_fuzzy_marks(&app, word_ids)
+    word_bank::clear_fuzzy_marks(db.inner(), word_ids)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn backup_database(
+    db: tauri::State<'_, word_bank::Database>,
+    path: String,
+) -> Result<backup::BackupInfo, String> {
+    backup::backup_database(db.inner(), &path)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+async fn restore_database(
+    db: tauri::State<'_, word_bank::Database>,
+    cache: tauri::State<'_, word_bank::StudyCalendarCache>,
+    path: String,
+) -> Result<backup::BackupInfo, String> {
+    backup::restore_database(db.inner(), cache.inner(), &path)
         .await
         .map_err(|error| error.to_string())
 }
@@ -154,8 +616,13 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(word_bank::StudyCalendarCache::default())
+        .manage(word_bank::Database::default())
+        .manage(dictionary_import::ImportCancellation::default())
         .setup(|app| {
-            if let Err(error) = tauri::async_runtime::block_on(word_bank::init_database(&app.handle())) {
+            let db = app.state::<word_bank::Database>();
+            if let Err(error) =
+                tauri::async_runtime::block_on(word_bank::init_database(app.handle(), db.inner()))
+            {
                 eprintln!("Failed to initialize database: {error}");
             }
             let minimize_to_tray =
@@ -212,15 +679,54 @@ pub fn run() {
             list_word_lists,
             create_word_list,
             import_dictionary_csv,
+            preview_dictionary_csv,
+            import_dictionary_csv_bytes,
+            preview_dictionary_csv_bytes,
+            import_dictionary_spreadsheet,
+            preview_dictionary_spreadsheet,
+            import_dictionary_file,
+            cancel_dictionary_import,
+            get_dictionary_catalog,
+            set_dictionary_mirror,
+            download_dictionary,
+            export_word_list_csv,
+            list_word_definitions,
+            inspect_anki_package,
+            import_anki_package,
+            export_anki_package,
             set_active_word_list,
             clear_active_word_list,
             delete_word_list,
             allocate_learning_session,
+            create_learning_session,
+            get_learning_session,
+            advance_learning_session,
+            finish_learning_session,
+            get_session_profile,
+            update_session_profile,
+            get_study_clock,
+            update_study_clock,
+            get_study_stats,
+            list_study_log,
+            summarize_study_log,
+            get_retention_by_interval,
+            get_mastery_history,
+            list_hardest_words,
+            get_answer_latency,
+            forecast_reviews,
+            get_study_goal,
+            update_study_goal,
+            add_streak_freeze,
+            remove_streak_freeze,
             increment_proficiency,
             decrement_proficiency,
+            grade_word,
+            undo_last_grade,
             list_daily_study_counts,
             list_fuzzy_words,
             clear_fuzzy_marks,
+            backup_database,
+            restore_database,
             set_tray_tooltip
         ])
         .run(tauri::generate_context!())
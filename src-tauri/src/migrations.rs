@@ -0,0 +1,820 @@
+use anyhow::{bail, Context, Result};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::word_bank::FUZZY_WORD_LIST_NAME;
+
+/// Schema version written by this build. Bump it together with a new arm in `apply`.
+pub const SCHEMA_VERSION: i64 = 10;
+
+/// Brings the database up to `SCHEMA_VERSION`, one transaction per step.
+///
+/// Databases created before versioning report `user_version = 0` and may already
+/// contain some of the later tables or columns, so every step is idempotent.
+pub async fn migrate(pool: &SqlitePool) -> Result<()> {
+    let current = read_version(pool).await?;
+    if current > SCHEMA_VERSION {
+        bail!(
+            "数据库版本为 {current}，高于当前应用支持的版本 {SCHEMA_VERSION}，请升级应用后再打开"
+        );
+    }
+
+    for version in (current + 1)..=SCHEMA_VERSION {
+        let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+        apply(&mut tx, version)
+            .await
+            .with_context(|| format!("Failed to migrate database to version {version}"))?;
+        sqlx::query(&format!("PRAGMA user_version = {version}"))
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update schema version")?;
+        tx.commit().await.context("提交数据库事务失败")?;
+    }
+
+    Ok(())
+}
+
+pub async fn read_version(pool: &SqlitePool) -> Result<i64> {
+    sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await
+        .context("Failed to read schema version")
+}
+
+async fn apply(conn: &mut SqliteConnection, version: i64) -> Result<()> {
+    match version {
+        1 => create_base_schema(conn).await,
+        2 => add_review_schedule(conn).await,
+        3 => add_study_log_grade(conn).await,
+        4 => add_session_profile(conn).await,
+        5 => add_learning_sessions(conn).await,
+        6 => add_grade_history(conn).await,
+        7 => add_dictionary_provenance(conn).await,
+        8 => add_study_clock(conn).await,
+        9 => add_study_goals(conn).await,
+        10 => add_study_event_details(conn).await,
+        _ => bail!("Unknown schema version {version}"),
+    }
+}
+
+async fn execute(conn: &mut SqliteConnection, sql: &str) -> Result<()> {
+    sqlx::query(sql)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to execute migration statement: {}", sql.trim()))?;
+    Ok(())
+}
+
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to read {table} schema"))
+}
+
+async fn add_column_if_missing(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    let columns = table_columns(conn, table).await?;
+    if !columns.iter().any(|name| name == column) {
+        execute(
+            conn,
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn create_base_schema(conn: &mut SqliteConnection) -> Result<()> {
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS word_list (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL UNIQUE,
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS word (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  word TEXT NOT NULL UNIQUE,
+  phonetic TEXT,
+  part_of_speech_and_meanings TEXT,
+  example_sentence TEXT,
+  example_translation TEXT,
+  audio_uk TEXT,
+  audio_us TEXT,
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS word_list_map (
+  word_list_id INTEGER NOT NULL,
+  word_id INTEGER NOT NULL,
+  PRIMARY KEY (word_list_id, word_id),
+  FOREIGN KEY (word_list_id) REFERENCES word_list(id),
+  FOREIGN KEY (word_id) REFERENCES word(id)
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS word_list_state (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  active_word_list_id INTEGER,
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        "INSERT OR IGNORE INTO word_list_state (id, active_word_list_id) VALUES (1, NULL)",
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS user_word_learning (
+  word_id INTEGER PRIMARY KEY,
+  proficiency_score INTEGER NOT NULL DEFAULT 0,
+  last_learned_at TEXT,
+  learn_count INTEGER NOT NULL DEFAULT 0,
+  is_fuzzy INTEGER NOT NULL DEFAULT 0,
+  fuzzy_marked_at TEXT,
+  FOREIGN KEY (word_id) REFERENCES word(id)
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        "CREATE INDEX IF NOT EXISTS idx_user_word_learning_score ON user_word_learning(proficiency_score)",
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS study_log (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  word_id INTEGER NOT NULL,
+  learned_at TEXT NOT NULL DEFAULT (datetime('now')),
+  FOREIGN KEY (word_id) REFERENCES word(id)
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        "CREATE INDEX IF NOT EXISTS idx_study_log_learned_at ON study_log(learned_at)",
+    )
+    .await?;
+
+    // The earliest builds named these columns last_studied_at / study_count.
+    let columns = table_columns(conn, "user_word_learning").await?;
+    add_column_if_missing(conn, "user_word_learning", "last_learned_at", "TEXT").await?;
+    add_column_if_missing(
+        conn,
+        "user_word_learning",
+        "learn_count",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+    .await?;
+    add_column_if_missing(
+        conn,
+        "user_word_learning",
+        "is_fuzzy",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+    .await?;
+    add_column_if_missing(conn, "user_word_learning", "fuzzy_marked_at", "TEXT").await?;
+
+    if columns.iter().any(|name| name == "last_studied_at") {
+        execute(
+            conn,
+            r#"
+UPDATE user_word_learning
+SET last_learned_at = COALESCE(last_learned_at, last_studied_at)
+WHERE last_studied_at IS NOT NULL
+"#,
+        )
+        .await?;
+    }
+
+    if columns.iter().any(|name| name == "study_count") {
+        execute(
+            conn,
+            r#"
+UPDATE user_word_learning
+SET learn_count = CASE
+  WHEN learn_count IS NULL OR learn_count = 0 THEN study_count
+  ELSE learn_count
+END
+WHERE study_count IS NOT NULL
+"#,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn add_review_schedule(conn: &mut SqliteConnection) -> Result<()> {
+    let scheduling_columns = [
+        ("ease_factor", "REAL NOT NULL DEFAULT 2.5"),
+        ("interval_days", "REAL NOT NULL DEFAULT 0"),
+        ("repetitions", "INTEGER NOT NULL DEFAULT 0"),
+        ("lapses", "INTEGER NOT NULL DEFAULT 0"),
+        ("due_at", "TEXT"),
+    ];
+    for (column, definition) in scheduling_columns {
+        add_column_if_missing(conn, "user_word_learning", column, definition).await?;
+    }
+
+    execute(
+        conn,
+        "CREATE INDEX IF NOT EXISTS idx_user_word_learning_due_at ON user_word_learning(due_at)",
+    )
+    .await?;
+
+    // Words learned before the scheduler existed only have a 0-10 score.
+    // Seed SM-2 state from it so current progress keeps its spacing.
+    execute(
+        conn,
+        r#"
+UPDATE user_word_learning
+SET repetitions = proficiency_score,
+    interval_days = CASE
+      WHEN proficiency_score <= 0 THEN 0
+      WHEN proficiency_score = 1 THEN 1
+      WHEN proficiency_score = 2 THEN 3
+      WHEN proficiency_score = 3 THEN 6
+      WHEN proficiency_score = 4 THEN 10
+      WHEN proficiency_score = 5 THEN 15
+      WHEN proficiency_score = 6 THEN 25
+      WHEN proficiency_score = 7 THEN 40
+      WHEN proficiency_score = 8 THEN 60
+      WHEN proficiency_score = 9 THEN 90
+      ELSE 120
+    END
+WHERE due_at IS NULL
+  AND (proficiency_score > 0 OR learn_count > 0)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+UPDATE user_word_learning
+SET due_at = datetime(
+  COALESCE(last_learned_at, datetime('now')),
+  '+' || CAST(interval_days AS INTEGER) || ' days'
+)
+WHERE due_at IS NULL
+  AND (proficiency_score > 0 OR learn_count > 0)
+"#,
+    )
+    .await
+}
+
+async fn add_study_log_grade(conn: &mut SqliteConnection) -> Result<()> {
+    add_column_if_missing(conn, "study_log", "grade", "TEXT").await
+}
+
+async fn add_session_profile(conn: &mut SqliteConnection) -> Result<()> {
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS session_profile (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  session_size INTEGER NOT NULL,
+  new_word_quota INTEGER NOT NULL,
+  review_quota INTEGER NOT NULL,
+  low_quota INTEGER NOT NULL,
+  mid_quota INTEGER NOT NULL,
+  high_quota INTEGER NOT NULL,
+  low_score_below INTEGER NOT NULL,
+  high_score_from INTEGER NOT NULL,
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+INSERT OR IGNORE INTO session_profile (
+  id,
+  session_size,
+  new_word_quota,
+  review_quota,
+  low_quota,
+  mid_quota,
+  high_quota,
+  low_score_below,
+  high_score_from
+)
+VALUES (1, 50, 20, 30, 20, 6, 4, 4, 9)
+"#,
+    )
+    .await
+}
+
+async fn add_learning_sessions(conn: &mut SqliteConnection) -> Result<()> {
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS learning_session (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  word_list_id INTEGER NOT NULL,
+  cursor INTEGER NOT NULL DEFAULT 0,
+  status TEXT NOT NULL DEFAULT 'active',
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+  finished_at TEXT,
+  FOREIGN KEY (word_list_id) REFERENCES word_list(id)
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS learning_session_item (
+  session_id INTEGER NOT NULL,
+  position INTEGER NOT NULL,
+  word_id INTEGER NOT NULL,
+  grade TEXT,
+  answered_at TEXT,
+  PRIMARY KEY (session_id, position),
+  FOREIGN KEY (session_id) REFERENCES learning_session(id),
+  FOREIGN KEY (word_id) REFERENCES word(id)
+)
+"#,
+    )
+    .await
+}
+
+async fn add_grade_history(conn: &mut SqliteConnection) -> Result<()> {
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS grade_history (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  word_id INTEGER NOT NULL,
+  study_log_id INTEGER,
+  had_learning_row INTEGER NOT NULL,
+  proficiency_score INTEGER NOT NULL,
+  last_learned_at TEXT,
+  learn_count INTEGER NOT NULL,
+  is_fuzzy INTEGER NOT NULL,
+  fuzzy_marked_at TEXT,
+  ease_factor REAL NOT NULL,
+  interval_days REAL NOT NULL,
+  repetitions INTEGER NOT NULL,
+  lapses INTEGER NOT NULL,
+  due_at TEXT,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  FOREIGN KEY (word_id) REFERENCES word(id)
+)
+"#,
+    )
+    .await
+}
+
+async fn add_dictionary_provenance(conn: &mut SqliteConnection) -> Result<()> {
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS dictionary_import (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  word_list_id INTEGER NOT NULL,
+  source_kind TEXT NOT NULL,
+  source_name TEXT,
+  merge_policy TEXT,
+  imported_at TEXT NOT NULL DEFAULT (datetime('now')),
+  FOREIGN KEY (word_list_id) REFERENCES word_list(id)
+)
+"#,
+    )
+    .await?;
+
+    // One definition per word and list, as last supplied by that list's dictionary.
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS word_definition (
+  word_id INTEGER NOT NULL,
+  word_list_id INTEGER NOT NULL,
+  import_id INTEGER NOT NULL,
+  phonetic TEXT,
+  part_of_speech_and_meanings TEXT,
+  example_sentence TEXT,
+  example_translation TEXT,
+  audio_uk TEXT,
+  audio_us TEXT,
+  updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+  PRIMARY KEY (word_id, word_list_id),
+  FOREIGN KEY (word_id) REFERENCES word(id),
+  FOREIGN KEY (word_list_id) REFERENCES word_list(id),
+  FOREIGN KEY (import_id) REFERENCES dictionary_import(id)
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        "CREATE INDEX IF NOT EXISTS idx_word_definition_list ON word_definition(word_list_id)",
+    )
+    .await?;
+
+    // Existing lists only know the shared definition, so attribute it to every
+    // dictionary list the word belongs to.
+    sqlx::query(
+        r#"
+INSERT INTO dictionary_import (word_list_id, source_kind, source_name, imported_at)
+SELECT wl.id, 'legacy', wl.name, wl.created_at
+FROM word_list wl
+WHERE wl.name != ?
+  AND NOT EXISTS (SELECT 1 FROM dictionary_import di WHERE di.word_list_id = wl.id)
+"#,
+    )
+    .bind(FUZZY_WORD_LIST_NAME)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to record legacy dictionary imports")?;
+
+    execute(
+        conn,
+        r#"
+INSERT OR IGNORE INTO word_definition (
+  word_id,
+  word_list_id,
+  import_id,
+  phonetic,
+  part_of_speech_and_meanings,
+  example_sentence,
+  example_translation,
+  audio_uk,
+  audio_us,
+  updated_at
+)
+SELECT
+  w.id,
+  wlm.word_list_id,
+  di.id,
+  w.phonetic,
+  w.part_of_speech_and_meanings,
+  w.example_sentence,
+  w.example_translation,
+  w.audio_uk,
+  w.audio_us,
+  di.imported_at
+FROM word_list_map wlm
+JOIN word w ON w.id = wlm.word_id
+JOIN dictionary_import di ON di.word_list_id = wlm.word_list_id AND di.source_kind = 'legacy'
+"#,
+    )
+    .await
+}
+
+async fn add_study_clock(conn: &mut SqliteConnection) -> Result<()> {
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS study_clock (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  utc_offset_minutes INTEGER NOT NULL,
+  day_start_hour INTEGER NOT NULL,
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+)
+"#,
+    )
+    .await?;
+
+    // Zero offset and midnight keep the UTC days earlier builds grouped by.
+    execute(
+        conn,
+        "INSERT OR IGNORE INTO study_clock (id, utc_offset_minutes, day_start_hour) VALUES (1, 0, 0)",
+    )
+    .await
+}
+
+async fn add_study_goals(conn: &mut SqliteConnection) -> Result<()> {
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS study_goal (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  new_word_target INTEGER NOT NULL,
+  review_target INTEGER NOT NULL,
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+)
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        "INSERT OR IGNORE INTO study_goal (id, new_word_target, review_target) VALUES (1, 20, 30)",
+    )
+    .await?;
+
+    execute(
+        conn,
+        r#"
+CREATE TABLE IF NOT EXISTS streak_freeze (
+  day TEXT PRIMARY KEY,
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+)
+"#,
+    )
+    .await
+}
+
+async fn add_study_event_details(conn: &mut SqliteConnection) -> Result<()> {
+    for (column, definition) in [
+        ("event_type", "TEXT"),
+        ("previous_score", "INTEGER"),
+        ("new_score", "INTEGER"),
+        ("response_ms", "INTEGER"),
+        ("session_id", "INTEGER"),
+        ("study_mode", "TEXT"),
+    ] {
+        add_column_if_missing(conn, "study_log", column, definition).await?;
+    }
+
+    // Older rows never recorded what produced them. Heuristic: the know and
+    // don't-know buttons always logged "good" and "again", so those rows are
+    // treated as button presses. Any other row is of unknown origin and keeps
+    // a NULL event type.
+    execute(
+        conn,
+        r#"
+UPDATE study_log
+SET event_type = CASE grade WHEN 'again' THEN 'incorrect' WHEN 'good' THEN 'correct' END
+WHERE event_type IS NULL
+"#,
+    )
+    .await?;
+
+    execute(
+        conn,
+        "CREATE INDEX IF NOT EXISTS idx_study_log_word ON study_log(word_id)",
+    )
+    .await?;
+    execute(
+        conn,
+        "CREATE INDEX IF NOT EXISTS idx_study_log_session ON study_log(session_id)",
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use tauri::async_runtime;
+
+    async fn empty_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(":memory:")
+                    .create_if_missing(true),
+            )
+            .await
+            .expect("Failed to open test database")
+    }
+
+    async fn run_script(pool: &SqlitePool, statements: &[&str]) {
+        for statement in statements {
+            sqlx::query(statement)
+                .execute(pool)
+                .await
+                .expect("Failed to prepare historical schema");
+        }
+    }
+
+    async fn assert_current_schema(pool: &SqlitePool) {
+        assert_eq!(read_version(pool).await.expect("version"), SCHEMA_VERSION);
+        let learning: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info('user_word_learning')")
+                .fetch_all(pool)
+                .await
+                .expect("Failed to read columns");
+        for column in [
+            "last_learned_at",
+            "learn_count",
+            "is_fuzzy",
+            "due_at",
+            "ease_factor",
+        ] {
+            assert!(
+                learning.iter().any(|name| name == column),
+                "missing {column}"
+            );
+        }
+        let study_log: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info('study_log')")
+                .fetch_all(pool)
+                .await
+                .expect("Failed to read columns");
+        for column in ["grade", "event_type", "response_ms", "session_id", "study_mode"] {
+            assert!(
+                study_log.iter().any(|name| name == column),
+                "missing {column}"
+            );
+        }
+        for table in [
+            "session_profile",
+            "learning_session",
+            "grade_history",
+            "word_definition",
+            "study_clock",
+            "study_goal",
+            "streak_freeze",
+        ] {
+            let exists: Option<String> = sqlx::query_scalar(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+            )
+            .bind(table)
+            .fetch_optional(pool)
+            .await
+            .expect("Failed to read tables");
+            assert!(exists.is_some(), "missing {table}");
+        }
+    }
+
+    const LEGACY_TABLES: [&str; 4] = [
+        "CREATE TABLE word_list (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL UNIQUE, created_at TEXT NOT NULL DEFAULT (datetime('now')))",
+        "CREATE TABLE word (id INTEGER PRIMARY KEY AUTOINCREMENT, word TEXT NOT NULL UNIQUE, phonetic TEXT, part_of_speech_and_meanings TEXT, example_sentence TEXT, example_translation TEXT, audio_uk TEXT, audio_us TEXT, created_at TEXT NOT NULL DEFAULT (datetime('now')))",
+        "CREATE TABLE word_list_map (word_list_id INTEGER NOT NULL, word_id INTEGER NOT NULL, PRIMARY KEY (word_list_id, word_id))",
+        "INSERT INTO word (word) VALUES ('alpha')",
+    ];
+
+    #[test]
+    fn upgrades_study_count_schema() {
+        async_runtime::block_on(async {
+            let pool = empty_pool().await;
+            run_script(&pool, &LEGACY_TABLES).await;
+            run_script(
+                &pool,
+                &[
+                    "CREATE TABLE user_word_learning (word_id INTEGER PRIMARY KEY, proficiency_score INTEGER NOT NULL DEFAULT 0, last_studied_at TEXT, study_count INTEGER)",
+                    "INSERT INTO user_word_learning (word_id, proficiency_score, last_studied_at, study_count) VALUES (1, 2, '2024-03-01 10:00:00', 7)",
+                    "UPDATE word SET part_of_speech_and_meanings = 'n. 阿尔法' WHERE id = 1",
+                    "INSERT INTO word_list (name) VALUES ('CET-4')",
+                    "INSERT INTO word_list_map (word_list_id, word_id) VALUES (1, 1)",
+                ],
+            )
+            .await;
+
+            migrate(&pool).await.expect("Failed to migrate");
+            assert_current_schema(&pool).await;
+
+            let row: (Option<String>, i64, Option<String>) = sqlx::query_as(
+                "SELECT last_learned_at, learn_count, due_at FROM user_word_learning WHERE word_id = 1",
+            )
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to read migrated row");
+            assert_eq!(row.0.as_deref(), Some("2024-03-01 10:00:00"));
+            assert_eq!(row.1, 7);
+            assert_eq!(row.2.as_deref(), Some("2024-03-04 10:00:00"));
+
+            let definition: (String, Option<String>) = sqlx::query_as(
+                r#"
+SELECT di.source_name, wd.part_of_speech_and_meanings
+FROM word_definition wd
+JOIN dictionary_import di ON di.id = wd.import_id
+WHERE wd.word_id = 1
+"#,
+            )
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to read backfilled definition");
+            assert_eq!(definition.0, "CET-4");
+            assert_eq!(definition.1.as_deref(), Some("n. 阿尔法"));
+        });
+    }
+
+    #[test]
+    fn upgrades_unversioned_fuzzy_schema() {
+        async_runtime::block_on(async {
+            let pool = empty_pool().await;
+            run_script(&pool, &LEGACY_TABLES).await;
+            run_script(
+                &pool,
+                &[
+                    "CREATE TABLE word_list_state (id INTEGER PRIMARY KEY CHECK (id = 1), active_word_list_id INTEGER, updated_at TEXT NOT NULL DEFAULT (datetime('now')))",
+                    "CREATE TABLE user_word_learning (word_id INTEGER PRIMARY KEY, proficiency_score INTEGER NOT NULL DEFAULT 0, last_learned_at TEXT, learn_count INTEGER NOT NULL DEFAULT 0, is_fuzzy INTEGER NOT NULL DEFAULT 0, fuzzy_marked_at TEXT)",
+                    "CREATE TABLE study_log (id INTEGER PRIMARY KEY AUTOINCREMENT, word_id INTEGER NOT NULL, learned_at TEXT NOT NULL DEFAULT (datetime('now')))",
+                    "INSERT INTO user_word_learning (word_id, proficiency_score, last_learned_at, learn_count, is_fuzzy) VALUES (1, 3, '2024-01-01 08:00:00', 4, 1)",
+                    "INSERT INTO study_log (word_id, learned_at) VALUES (1, '2024-01-01 08:00:00')",
+                ],
+            )
+            .await;
+
+            migrate(&pool).await.expect("Failed to migrate");
+            assert_current_schema(&pool).await;
+
+            let row: (i64, f64, Option<String>, i64) = sqlx::query_as(
+                "SELECT repetitions, interval_days, due_at, is_fuzzy FROM user_word_learning WHERE word_id = 1",
+            )
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to read migrated row");
+            assert_eq!(row, (3, 6.0, Some("2024-01-07 08:00:00".to_string()), 1));
+            let logs: i64 =
+                sqlx::query_scalar("SELECT COUNT(1) FROM study_log WHERE grade IS NULL")
+                    .fetch_one(&pool)
+                    .await
+                    .expect("Failed to count logs");
+            assert_eq!(logs, 1);
+        });
+    }
+
+    #[test]
+    fn infers_legacy_study_events_from_grades() {
+        async_runtime::block_on(async {
+            let pool = empty_pool().await;
+            run_script(&pool, &LEGACY_TABLES).await;
+            run_script(
+                &pool,
+                &[
+                    "CREATE TABLE study_log (id INTEGER PRIMARY KEY AUTOINCREMENT, word_id INTEGER NOT NULL, learned_at TEXT NOT NULL DEFAULT (datetime('now')), grade TEXT)",
+                    "INSERT INTO study_log (word_id, grade) VALUES (1, 'again'), (1, 'good'), (1, 'hard'), (1, NULL)",
+                ],
+            )
+            .await;
+
+            migrate(&pool).await.expect("Failed to migrate");
+
+            let events: Vec<Option<String>> =
+                sqlx::query_scalar("SELECT event_type FROM study_log ORDER BY id")
+                    .fetch_all(&pool)
+                    .await
+                    .expect("Failed to read events");
+            assert_eq!(
+                events,
+                [Some("incorrect".to_string()), Some("correct".to_string()), None, None]
+            );
+        });
+    }
+
+    #[test]
+    fn reruns_on_unversioned_current_schema() {
+        async_runtime::block_on(async {
+            let pool = empty_pool().await;
+            migrate(&pool).await.expect("Failed to create schema");
+            sqlx::query("PRAGMA user_version = 0")
+                .execute(&pool)
+                .await
+                .expect("Failed to reset version");
+            migrate(&pool).await.expect("Failed to rerun migrations");
+            assert_current_schema(&pool).await;
+        });
+    }
+
+    #[test]
+    fn rejects_newer_schema() {
+        async_runtime::block_on(async {
+            let pool = empty_pool().await;
+            sqlx::query(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1))
+                .execute(&pool)
+                .await
+                .expect("Failed to set version");
+            let error = migrate(&pool).await.expect_err("Newer schema should fail");
+            assert!(error.to_string().contains("请升级应用"));
+        });
+    }
+}
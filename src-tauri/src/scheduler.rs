@@ -0,0 +1,187 @@
+use serde::Deserialize;
+
+pub const DEFAULT_EASE_FACTOR: f64 = 2.5;
+const MIN_EASE_FACTOR: f64 = 1.3;
+const MAX_INTERVAL_DAYS: f64 = 365.0;
+const RELEARN_INTERVAL_DAYS: f64 = 10.0 / 1440.0;
+const HARD_INTERVAL_MULTIPLIER: f64 = 1.2;
+
+/// Answer buttons shown to the learner, mapped onto SM-2 quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewGrade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl ReviewGrade {
+    /// SM-2 review quality, 0 (blackout) to 5 (perfect recall).
+    /// Anything below 3 counts as a lapse and restarts the card.
+    pub fn quality(self) -> u8 {
+        match self {
+            ReviewGrade::Again => 1,
+            ReviewGrade::Hard => 3,
+            ReviewGrade::Good => 4,
+            ReviewGrade::Easy => 5,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReviewGrade::Again => "again",
+            ReviewGrade::Hard => "hard",
+            ReviewGrade::Good => "good",
+            ReviewGrade::Easy => "easy",
+        }
+    }
+
+    /// Change applied to the 0-10 proficiency score shown on the card.
+    pub fn score_delta(self) -> i64 {
+        match self {
+            ReviewGrade::Again => -1,
+            ReviewGrade::Hard => 0,
+            ReviewGrade::Good => 1,
+            ReviewGrade::Easy => 2,
+        }
+    }
+
+    pub fn is_lapse(self) -> bool {
+        self == ReviewGrade::Again
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewState {
+    pub ease_factor: f64,
+    pub interval_days: f64,
+    pub repetitions: i64,
+    pub lapses: i64,
+}
+
+impl Default for ReviewState {
+    fn default() -> Self {
+        Self {
+            ease_factor: DEFAULT_EASE_FACTOR,
+            interval_days: 0.0,
+            repetitions: 0,
+            lapses: 0,
+        }
+    }
+}
+
+/// Computes the state after one review using the SM-2 algorithm.
+pub fn schedule(state: &ReviewState, quality: u8) -> ReviewState {
+    let quality = quality.min(5);
+    let penalty = f64::from(5 - quality);
+    let ease_factor =
+        (state.ease_factor + 0.1 - penalty * (0.08 + penalty * 0.02)).max(MIN_EASE_FACTOR);
+
+    if quality < 3 {
+        return ReviewState {
+            ease_factor,
+            interval_days: RELEARN_INTERVAL_DAYS,
+            repetitions: 0,
+            lapses: state.lapses + 1,
+        };
+    }
+
+    let repetitions = state.repetitions + 1;
+    let hard = quality == 3;
+    let interval_days = match repetitions {
+        // A hard recall of a young card moves it on, just not as far.
+        1 if hard => 1.0 / HARD_INTERVAL_MULTIPLIER,
+        2 if hard => 6.0 / HARD_INTERVAL_MULTIPLIER,
+        1 => 1.0,
+        2 => 6.0,
+        // A hard recall still grows the interval, but much slower than the ease factor.
+        _ if hard => (state.interval_days.max(1.0) * HARD_INTERVAL_MULTIPLIER).round(),
+        _ => (state.interval_days.max(1.0) * ease_factor).round(),
+    };
+
+    ReviewState {
+        ease_factor,
+        interval_days: interval_days.min(MAX_INTERVAL_DAYS),
+        repetitions,
+        lapses: state.lapses,
+    }
+}
+
+/// SQLite `datetime` modifier that moves a timestamp forward by the interval.
+pub fn due_modifier(interval_days: f64) -> String {
+    let seconds = (interval_days.max(0.0) * 86_400.0).round() as i64;
+    format!("+{seconds} seconds")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn successful_reviews_follow_sm2_intervals() {
+        let first = schedule(&ReviewState::default(), ReviewGrade::Good.quality());
+        assert_eq!(first.repetitions, 1);
+        assert_eq!(first.interval_days, 1.0);
+
+        let second = schedule(&first, ReviewGrade::Good.quality());
+        assert_eq!(second.interval_days, 6.0);
+
+        let third = schedule(&second, ReviewGrade::Good.quality());
+        assert_eq!(third.interval_days, (6.0 * third.ease_factor).round());
+        assert_eq!(third.ease_factor, DEFAULT_EASE_FACTOR);
+    }
+
+    #[test]
+    fn lapse_resets_repetitions_and_lowers_ease() {
+        let state = ReviewState {
+            ease_factor: 1.4,
+            interval_days: 30.0,
+            repetitions: 5,
+            lapses: 0,
+        };
+        let next = schedule(&state, ReviewGrade::Again.quality());
+        assert_eq!(next.repetitions, 0);
+        assert_eq!(next.lapses, 1);
+        assert_eq!(next.ease_factor, MIN_EASE_FACTOR);
+        assert!(next.interval_days < 1.0);
+    }
+
+    #[test]
+    fn hard_grows_interval_slower_than_good() {
+        let state = ReviewState {
+            ease_factor: DEFAULT_EASE_FACTOR,
+            interval_days: 10.0,
+            repetitions: 3,
+            lapses: 0,
+        };
+        let hard = schedule(&state, ReviewGrade::Hard.quality());
+        let good = schedule(&state, ReviewGrade::Good.quality());
+        let easy = schedule(&state, ReviewGrade::Easy.quality());
+        assert_eq!(hard.interval_days, 12.0);
+        assert!(hard.ease_factor < state.ease_factor);
+        assert!(hard.interval_days < good.interval_days);
+        assert!(good.interval_days < easy.interval_days);
+        assert_eq!(hard.lapses, 0);
+
+        let mut young = ReviewState::default();
+        for _ in 0..2 {
+            let hard = schedule(&young, ReviewGrade::Hard.quality());
+            let good = schedule(&young, ReviewGrade::Good.quality());
+            assert!(hard.interval_days < good.interval_days);
+            young = good;
+        }
+    }
+
+    #[test]
+    fn intervals_are_capped() {
+        let state = ReviewState {
+            ease_factor: 3.0,
+            interval_days: 300.0,
+            repetitions: 10,
+            lapses: 0,
+        };
+        assert_eq!(schedule(&state, 5).interval_days, MAX_INTERVAL_DAYS);
+        assert_eq!(due_modifier(1.0), "+86400 seconds");
+    }
+}
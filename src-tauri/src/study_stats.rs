@@ -0,0 +1,614 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::word_bank::{read_study_clock, Database, StudyEventKind};
+
+const MAX_DAILY_TARGET: i64 = 1000;
+const DEFAULT_LOG_PAGE: i64 = 100;
+const MAX_LOG_PAGE: i64 = 1000;
+
+/// Daily targets; a word counts as new on the study day it first appears in `study_log`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StudyGoal {
+    pub new_word_target: i64,
+    pub review_target: i64,
+}
+
+impl Default for StudyGoal {
+    fn default() -> Self {
+        Self {
+            new_word_target: 20,
+            review_target: 30,
+        }
+    }
+}
+
+impl StudyGoal {
+    fn validate(&self) -> Result<()> {
+        let range = 0..=MAX_DAILY_TARGET;
+        if !range.contains(&self.new_word_target) || !range.contains(&self.review_target) {
+            bail!("每日目标需在 0 到 {MAX_DAILY_TARGET} 之间");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StudyStats {
+    /// Current study day as `YYYY-MM-DD`, following the study clock.
+    pub today: String,
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    pub new_words_today: i64,
+    pub reviews_today: i64,
+    pub goal: StudyGoal,
+    pub goal_met: bool,
+    pub freeze_days: Vec<String>,
+}
+
+/// Filters over `study_log`; `from_day` and `to_day` are inclusive study days.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StudyLogQuery {
+    pub word_id: Option<i64>,
+    pub session_id: Option<i64>,
+    pub event_type: Option<StudyEventKind>,
+    pub study_mode: Option<String>,
+    pub from_day: Option<String>,
+    pub to_day: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StudyLogEntry {
+    pub id: i64,
+    pub word_id: i64,
+    pub word: String,
+    pub learned_at: String,
+    pub study_day: String,
+    /// `None` for rows logged before event types were recorded.
+    pub event_type: Option<String>,
+    pub grade: Option<String>,
+    pub previous_score: Option<i64>,
+    pub new_score: Option<i64>,
+    pub response_ms: Option<i64>,
+    pub session_id: Option<i64>,
+    pub study_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StudyLogPage {
+    pub total: i64,
+    pub entries: Vec<StudyLogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StudyLogSummary {
+    pub event_count: i64,
+    pub word_count: i64,
+    /// Answers graded anything but "again".
+    pub correct_count: i64,
+    pub incorrect_count: i64,
+    pub average_response_ms: Option<f64>,
+}
+
+/// Returns `(current, longest)` streaks over Julian day numbers.
+///
+/// Frozen days bridge a gap without adding to the streak, and a today without
+/// study yet does not break the current streak until the day is over.
+fn streaks(studied: &BTreeSet<i64>, frozen: &BTreeSet<i64>, today: i64) -> (i64, i64) {
+    let mut current = 0;
+    let mut day = today;
+    if !studied.contains(&day) {
+        day -= 1;
+    }
+    while studied.contains(&day) || frozen.contains(&day) {
+        current += i64::from(studied.contains(&day));
+        day -= 1;
+    }
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<i64> = None;
+    for &day in studied.union(frozen) {
+        if previous.is_some_and(|previous| previous + 1 != day) {
+            run = 0;
+        }
+        run += i64::from(studied.contains(&day));
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    (current, longest.max(current))
+}
+
+pub(crate) async fn read_study_goal(pool: &SqlitePool) -> Result<StudyGoal> {
+    let row = sqlx::query("SELECT new_word_target, review_target FROM study_goal WHERE id = 1")
+        .fetch_optional(pool)
+        .await
+        .context("读取每日目标失败")?;
+    let Some(row) = row else {
+        return Ok(StudyGoal::default());
+    };
+    Ok(StudyGoal {
+        new_word_target: row.try_get("new_word_target").context("读取新词目标失败")?,
+        review_target: row.try_get("review_target").context("读取复习目标失败")?,
+    })
+}
+
+async fn write_study_goal(pool: &SqlitePool, goal: &StudyGoal) -> Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO study_goal (id, new_word_target, review_target, updated_at) VALUES (1, ?, ?, datetime('now'))",
+    )
+    .bind(goal.new_word_target)
+    .bind(goal.review_target)
+    .execute(pool)
+    .await
+    .context("保存每日目标失败")?;
+    Ok(())
+}
+
+async fn list_freeze_days(pool: &SqlitePool) -> Result<Vec<String>> {
+    sqlx::query_scalar("SELECT day FROM streak_freeze ORDER BY day ASC")
+        .fetch_all(pool)
+        .await
+        .context("读取补签日期失败")
+}
+
+/// Normalises `day` to `YYYY-MM-DD`, rejecting anything SQLite cannot parse as a date.
+async fn parse_day(pool: &SqlitePool, day: &str) -> Result<String> {
+    let parsed: Option<String> = sqlx::query_scalar("SELECT date(?)")
+        .bind(day.trim())
+        .fetch_one(pool)
+        .await
+        .context("解析日期失败")?;
+    match parsed {
+        Some(parsed) if parsed == day.trim() => Ok(parsed),
+        _ => bail!("日期格式需为 YYYY-MM-DD"),
+    }
+}
+
+async fn study_stats_with_pool(pool: &SqlitePool) -> Result<StudyStats> {
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let goal = read_study_goal(pool).await?;
+
+    let row = sqlx::query(
+        "SELECT date('now', ?) AS today, CAST(julianday(date('now', ?)) AS INTEGER) AS today_number",
+    )
+    .bind(&day_modifier)
+    .bind(&day_modifier)
+    .fetch_one(pool)
+    .await
+    .context("读取学习日失败")?;
+    let today: String = row.try_get("today").context("读取学习日失败")?;
+    let today_number: i64 = row.try_get("today_number").context("读取学习日失败")?;
+
+    let studied: Vec<i64> = sqlx::query_scalar(
+        "SELECT DISTINCT CAST(julianday(date(learned_at, ?)) AS INTEGER) FROM study_log",
+    )
+    .bind(&day_modifier)
+    .fetch_all(pool)
+    .await
+    .context("读取学习记录失败")?;
+    let frozen: Vec<i64> =
+        sqlx::query_scalar("SELECT CAST(julianday(day) AS INTEGER) FROM streak_freeze")
+            .fetch_all(pool)
+            .await
+            .context("读取补签日期失败")?;
+    let (current_streak, longest_streak) = streaks(
+        &studied.into_iter().collect(),
+        &frozen.into_iter().collect(),
+        today_number,
+    );
+
+    let row = sqlx::query(
+        r#"
+WITH first_seen AS (
+  SELECT word_id, MIN(learned_at) AS first_learned_at
+  FROM study_log
+  GROUP BY word_id
+),
+today_words AS (
+  SELECT DISTINCT word_id
+  FROM study_log
+  WHERE date(learned_at, ?) = ?
+)
+SELECT
+  COUNT(CASE WHEN date(fs.first_learned_at, ?) = ? THEN 1 END) AS new_words,
+  COUNT(CASE WHEN date(fs.first_learned_at, ?) < ? THEN 1 END) AS reviews
+FROM today_words tw
+JOIN first_seen fs ON fs.word_id = tw.word_id
+"#,
+    )
+    .bind(&day_modifier)
+    .bind(&today)
+    .bind(&day_modifier)
+    .bind(&today)
+    .bind(&day_modifier)
+    .bind(&today)
+    .fetch_one(pool)
+    .await
+    .context("统计今日学习进度失败")?;
+    let new_words_today: i64 = row.try_get("new_words").context("读取今日新词数量失败")?;
+    let reviews_today: i64 = row.try_get("reviews").context("读取今日复习数量失败")?;
+
+    Ok(StudyStats {
+        today,
+        current_streak,
+        longest_streak,
+        new_words_today,
+        reviews_today,
+        goal,
+        goal_met: new_words_today >= goal.new_word_target && reviews_today >= goal.review_target,
+        freeze_days: list_freeze_days(pool).await?,
+    })
+}
+
+async fn check_log_days(pool: &SqlitePool, query: &StudyLogQuery) -> Result<()> {
+    for day in [query.from_day.as_deref(), query.to_day.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        parse_day(pool, day).await?;
+    }
+    Ok(())
+}
+
+fn push_log_filters(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    query: &StudyLogQuery,
+    day_modifier: &str,
+) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(word_id) = query.word_id {
+        builder.push(" AND sl.word_id = ").push_bind(word_id);
+    }
+    if let Some(session_id) = query.session_id {
+        builder.push(" AND sl.session_id = ").push_bind(session_id);
+    }
+    if let Some(kind) = query.event_type {
+        builder
+            .push(" AND sl.event_type = ")
+            .push_bind(kind.as_str());
+    }
+    if let Some(mode) = query.study_mode.as_deref() {
+        builder
+            .push(" AND sl.study_mode = ")
+            .push_bind(mode.trim().to_string());
+    }
+    if let Some(from_day) = query.from_day.as_deref() {
+        builder
+            .push(" AND date(sl.learned_at, ")
+            .push_bind(day_modifier.to_string())
+            .push(") >= ")
+            .push_bind(from_day.to_string());
+    }
+    if let Some(to_day) = query.to_day.as_deref() {
+        builder
+            .push(" AND date(sl.learned_at, ")
+            .push_bind(day_modifier.to_string())
+            .push(") <= ")
+            .push_bind(to_day.to_string());
+    }
+}
+
+async fn list_study_log_with_pool(
+    pool: &SqlitePool,
+    query: &StudyLogQuery,
+) -> Result<StudyLogPage> {
+    check_log_days(pool, query).await?;
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LOG_PAGE)
+        .clamp(1, MAX_LOG_PAGE);
+    let offset = query.offset.unwrap_or_default().max(0);
+
+    let mut builder = QueryBuilder::new("SELECT COUNT(1) FROM study_log sl");
+    push_log_filters(&mut builder, query, &day_modifier);
+    let total: i64 = builder
+        .build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .context("统计学习记录失败")?;
+
+    let mut builder = QueryBuilder::new(
+        r#"
+SELECT
+  sl.id,
+  sl.word_id,
+  w.word,
+  sl.learned_at,
+  date(sl.learned_at, "#,
+    );
+    builder.push_bind(day_modifier.clone()).push(
+        r#") AS study_day,
+  sl.event_type,
+  sl.grade,
+  sl.previous_score,
+  sl.new_score,
+  sl.response_ms,
+  sl.session_id,
+  sl.study_mode
+FROM study_log sl
+JOIN word w ON w.id = sl.word_id"#,
+    );
+    push_log_filters(&mut builder, query, &day_modifier);
+    builder
+        .push(" ORDER BY sl.learned_at DESC, sl.id DESC LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    let rows = builder
+        .build()
+        .fetch_all(pool)
+        .await
+        .context("读取学习记录失败")?;
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for row in rows {
+        entries.push(StudyLogEntry {
+            id: row.try_get("id").context("读取学习记录 ID 失败")?,
+            word_id: row.try_get("word_id").context("读取单词 ID 失败")?,
+            word: row.try_get("word").context("读取单词失败")?,
+            learned_at: row.try_get("learned_at").context("读取学习时间失败")?,
+            study_day: row.try_get("study_day").context("读取学习日失败")?,
+            event_type: row.try_get("event_type").context("读取事件类型失败")?,
+            grade: row.try_get("grade").context("读取评分失败")?,
+            previous_score: row.try_get("previous_score").context("读取原熟练度失败")?,
+            new_score: row.try_get("new_score").context("读取新熟练度失败")?,
+            response_ms: row.try_get("response_ms").context("读取作答用时失败")?,
+            session_id: row.try_get("session_id").context("读取学习会话失败")?,
+            study_mode: row.try_get("study_mode").context("读取学习模式失败")?,
+        });
+    }
+    Ok(StudyLogPage { total, entries })
+}
+
+async fn summarize_study_log_with_pool(
+    pool: &SqlitePool,
+    query: &StudyLogQuery,
+) -> Result<StudyLogSummary> {
+    check_log_days(pool, query).await?;
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let mut builder = QueryBuilder::new(
+        r#"
+SELECT
+  COUNT(1) AS event_count,
+  COUNT(DISTINCT sl.word_id) AS word_count,
+  COUNT(CASE WHEN sl.grade IS NOT NULL AND sl.grade != 'again' THEN 1 END) AS correct_count,
+  COUNT(CASE WHEN sl.grade = 'again' THEN 1 END) AS incorrect_count,
+  AVG(sl.response_ms) AS average_response_ms
+FROM study_log sl"#,
+    );
+    push_log_filters(&mut builder, query, &day_modifier);
+    let row = builder
+        .build()
+        .fetch_one(pool)
+        .await
+        .context("统计学习记录失败")?;
+    Ok(StudyLogSummary {
+        event_count: row.try_get("event_count").context("读取记录数量失败")?,
+        word_count: row.try_get("word_count").context("读取单词数量失败")?,
+        correct_count: row.try_get("correct_count").context("读取答对数量失败")?,
+        incorrect_count: row.try_get("incorrect_count").context("读取答错数量失败")?,
+        average_response_ms: row
+            .try_get("average_response_ms")
+            .context("读取平均用时失败")?,
+    })
+}
+
+pub async fn list_study_log(db: &Database, query: StudyLogQuery) -> Result<StudyLogPage> {
+    let pool = db.pool()?;
+    list_study_log_with_pool(&pool, &query).await
+}
+
+pub async fn summarize_study_log(db: &Database, query: StudyLogQuery) -> Result<StudyLogSummary> {
+    let pool = db.pool()?;
+    summarize_study_log_with_pool(&pool, &query).await
+}
+
+pub async fn get_study_stats(db: &Database) -> Result<StudyStats> {
+    let pool = db.pool()?;
+    study_stats_with_pool(&pool).await
+}
+
+pub async fn get_study_goal(db: &Database) -> Result<StudyGoal> {
+    let pool = db.pool()?;
+    read_study_goal(&pool).await
+}
+
+pub async fn update_study_goal(db: &Database, goal: StudyGoal) -> Result<StudyGoal> {
+    goal.validate()?;
+    let pool = db.pool()?;
+    write_study_goal(&pool, &goal).await?;
+    Ok(goal)
+}
+
+async fn add_streak_freeze_with_pool(pool: &SqlitePool, day: &str) -> Result<Vec<String>> {
+    let day = parse_day(pool, day).await?;
+    sqlx::query("INSERT OR IGNORE INTO streak_freeze (day) VALUES (?)")
+        .bind(&day)
+        .execute(pool)
+        .await
+        .context("保存补签日期失败")?;
+    list_freeze_days(pool).await
+}
+
+pub async fn add_streak_freeze(db: &Database, day: String) -> Result<Vec<String>> {
+    let pool = db.pool()?;
+    add_streak_freeze_with_pool(&pool, &day).await
+}
+
+pub async fn remove_streak_freeze(db: &Database, day: String) -> Result<Vec<String>> {
+    let pool = db.pool()?;
+    sqlx::query("DELETE FROM streak_freeze WHERE day = ?")
+        .bind(day.trim())
+        .execute(&pool)
+        .await
+        .context("删除补签日期失败")?;
+    list_freeze_days(&pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word_bank;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use tauri::async_runtime;
+
+    async fn setup_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(SqliteConnectOptions::new().filename(":memory:"))
+            .await
+            .expect("Failed to open test database");
+        word_bank::ensure_schema(&pool)
+            .await
+            .expect("Failed to ensure schema");
+        pool
+    }
+
+    async fn log_study(pool: &SqlitePool, word_id: i64, days_ago: i64) {
+        sqlx::query("INSERT INTO study_log (word_id, learned_at) VALUES (?, datetime('now', ?))")
+            .bind(word_id)
+            .bind(format!("-{days_ago} days"))
+            .execute(pool)
+            .await
+            .expect("Failed to insert study log");
+    }
+
+    #[test]
+    fn stats_follow_streaks_freezes_and_goals() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            sqlx::query("INSERT INTO word (id, word) VALUES (1, 'alpha'), (2, 'beta')")
+                .execute(&pool)
+                .await
+                .expect("Failed to insert words");
+            for days_ago in [9, 8, 7, 6, 3, 1] {
+                log_study(&pool, 1, days_ago).await;
+            }
+            log_study(&pool, 1, 0).await;
+            log_study(&pool, 2, 0).await;
+
+            let stats = study_stats_with_pool(&pool)
+                .await
+                .expect("Failed to read stats");
+            assert_eq!(stats.current_streak, 2);
+            assert_eq!(stats.longest_streak, 4);
+            assert_eq!((stats.new_words_today, stats.reviews_today), (1, 1));
+            assert!(!stats.goal_met);
+
+            let two_days_ago: String = sqlx::query_scalar("SELECT date('now', '-2 days')")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to compute date");
+            add_streak_freeze_with_pool(&pool, &two_days_ago)
+                .await
+                .expect("Failed to freeze day");
+            assert!(add_streak_freeze_with_pool(&pool, "2024-13-01")
+                .await
+                .is_err());
+
+            write_study_goal(
+                &pool,
+                &StudyGoal {
+                    new_word_target: 1,
+                    review_target: 1,
+                },
+            )
+            .await
+            .expect("Failed to save goal");
+
+            let stats = study_stats_with_pool(&pool)
+                .await
+                .expect("Failed to read stats");
+            assert_eq!(stats.current_streak, 3);
+            assert_eq!(stats.longest_streak, 4);
+            assert_eq!(stats.freeze_days, vec![two_days_ago]);
+            assert!(stats.goal_met);
+        });
+    }
+
+    #[test]
+    fn study_log_queries_filter_and_summarize() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            sqlx::query("INSERT INTO word (id, word) VALUES (1, 'alpha'), (2, 'beta')")
+                .execute(&pool)
+                .await
+                .expect("Failed to insert words");
+            sqlx::query(
+                r#"
+INSERT INTO study_log (word_id, learned_at, grade, event_type, response_ms, session_id, study_mode)
+VALUES
+  (1, '2024-05-01 09:00:00', 'good', 'correct', 1200, NULL, 'flashcard'),
+  (1, '2024-05-02 09:00:00', 'again', 'incorrect', 4000, NULL, 'flashcard'),
+  (2, '2024-05-02 10:00:00', 'easy', 'grade', NULL, 7, 'session'),
+  (2, '2024-04-01 10:00:00', NULL, NULL, NULL, NULL, NULL)
+"#,
+            )
+            .execute(&pool)
+            .await
+            .expect("Failed to insert study log");
+
+            let page = list_study_log_with_pool(
+                &pool,
+                &StudyLogQuery {
+                    from_day: Some("2024-05-01".to_string()),
+                    limit: Some(2),
+                    ..StudyLogQuery::default()
+                },
+            )
+            .await
+            .expect("Failed to list study log");
+            assert_eq!(page.total, 3);
+            let words: Vec<&str> = page
+                .entries
+                .iter()
+                .map(|entry| entry.word.as_str())
+                .collect();
+            assert_eq!(words, ["beta", "alpha"]);
+            assert_eq!(page.entries[0].session_id, Some(7));
+
+            let incorrect = list_study_log_with_pool(
+                &pool,
+                &StudyLogQuery {
+                    event_type: Some(StudyEventKind::Incorrect),
+                    ..StudyLogQuery::default()
+                },
+            )
+            .await
+            .expect("Failed to filter study log");
+            assert_eq!(incorrect.total, 1);
+            assert_eq!(incorrect.entries[0].study_day, "2024-05-02");
+
+            let summary = summarize_study_log_with_pool(
+                &pool,
+                &StudyLogQuery {
+                    study_mode: Some("flashcard".to_string()),
+                    ..StudyLogQuery::default()
+                },
+            )
+            .await
+            .expect("Failed to summarize study log");
+            assert_eq!((summary.event_count, summary.word_count), (2, 1));
+            assert_eq!((summary.correct_count, summary.incorrect_count), (1, 1));
+            assert_eq!(summary.average_response_ms, Some(2600.0));
+
+            assert!(list_study_log_with_pool(
+                &pool,
+                &StudyLogQuery {
+                    to_day: Some("May 2".to_string()),
+                    ..StudyLogQuery::default()
+                },
+            )
+            .await
+            .is_err());
+        });
+    }
+}
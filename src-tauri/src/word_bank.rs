@@ -1,6 +1,7 @@
-﻿use std::collections::HashSet;
+﻿use std::collections::HashMap;
 use std::fs;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
@@ -8,10 +9,19 @@ use serde::{Deserialize, Serialize};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::QueryBuilder;
 use sqlx::Row;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use tauri::Manager;
 
-const FUZZY_WORD_LIST_NAME: &str = "模糊词词库";
+use crate::backup;
+use crate::migrations;
+use crate::scheduler::{self, ReviewGrade, ReviewState};
+
+pub(crate) const FUZZY_WORD_LIST_NAME: &str = "模糊词词库";
+const MAX_SESSION_SIZE: i64 = 1000;
+const GRADE_HISTORY_LIMIT: i64 = 100;
+const MAX_STUDY_MODE_CHARS: usize = 32;
+/// Dictionary rows written per statement; keeps binds under SQLite's 999 limit.
+const UPSERT_CHUNK_ROWS: usize = 100;
 const REQUIRED_HEADERS: [&str; 7] = [
     "word",
     "phonetic",
@@ -21,6 +31,26 @@ const REQUIRED_HEADERS: [&str; 7] = [
     "audio_uk",
     "audio_us",
 ];
+const PROGRESS_HEADERS: [&str; 4] = ["proficiency", "learn_count", "fuzzy", "last_learned_at"];
+/// Word fields taken whole from the list's `word_definition` row (`wd`), or
+/// from the shared `word` row (`w`) when the list has none, so one card never
+/// mixes two dictionaries.
+pub(crate) const DEFINITION_COLUMNS: &str = r#"CASE WHEN wd.word_id IS NULL THEN w.phonetic ELSE wd.phonetic END AS phonetic,
+  CASE WHEN wd.word_id IS NULL THEN w.part_of_speech_and_meanings ELSE wd.part_of_speech_and_meanings END
+    AS part_of_speech_and_meanings,
+  CASE WHEN wd.word_id IS NULL THEN w.example_sentence ELSE wd.example_sentence END
+    AS example_sentence,
+  CASE WHEN wd.word_id IS NULL THEN w.example_translation ELSE wd.example_translation END
+    AS example_translation,
+  CASE WHEN wd.word_id IS NULL THEN w.audio_uk ELSE wd.audio_uk END AS audio_uk,
+  CASE WHEN wd.word_id IS NULL THEN w.audio_us ELSE wd.audio_us END AS audio_us"#;
+/// Names the list (`wl`) a definition came from, only when other lists also
+/// define the word; with a single dictionary there is nothing to tell apart.
+const DEFINITION_SOURCE_COLUMN: &str = r#"CASE
+    WHEN wd.word_id IS NULL THEN NULL
+    WHEN (SELECT COUNT(1) FROM word_definition other WHERE other.word_id = w.id) < 2 THEN NULL
+    ELSE wl.name
+  END AS definition_source"#;
 
 fn is_system_word_list_name(name: &str) -> bool {
     name == FUZZY_WORD_LIST_NAME
@@ -31,26 +61,136 @@ pub struct WordListCard {
     pub id: i64,
     pub name: String,
     pub word_count: i64,
+    pub learned_count: i64,
+    pub mastered_count: i64,
+    pub due_count: i64,
     pub is_active: bool,
     pub is_system: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct DictionaryCsvRecord {
-    word: String,
-    phonetic: Option<String>,
-    part_of_speech_and_meanings: Option<String>,
-    example_sentence: Option<String>,
-    example_translation: Option<String>,
-    audio_uk: Option<String>,
-    audio_us: Option<String>,
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DictionaryRecord {
+    pub(crate) word: String,
+    pub(crate) phonetic: Option<String>,
+    pub(crate) part_of_speech_and_meanings: Option<String>,
+    pub(crate) example_sentence: Option<String>,
+    pub(crate) example_translation: Option<String>,
+    pub(crate) audio_uk: Option<String>,
+    pub(crate) audio_us: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+impl DictionaryRecord {
+    /// Trims every value and drops blank optional fields.
+    pub(crate) fn normalized(self) -> Self {
+        Self {
+            word: self.word.trim().to_string(),
+            phonetic: normalize_optional(self.phonetic),
+            part_of_speech_and_meanings: normalize_optional(self.part_of_speech_and_meanings),
+            example_sentence: normalize_optional(self.example_sentence),
+            example_translation: normalize_optional(self.example_translation),
+            audio_uk: normalize_optional(self.audio_uk),
+            audio_us: normalize_optional(self.audio_us),
+        }
+    }
+
+    pub(crate) fn fields(&self) -> [(&'static str, Option<&str>); 6] {
+        [
+            ("phonetic", self.phonetic.as_deref()),
+            (
+                "part_of_speech_and_meanings",
+                self.part_of_speech_and_meanings.as_deref(),
+            ),
+            ("example_sentence", self.example_sentence.as_deref()),
+            ("example_translation", self.example_translation.as_deref()),
+            ("audio_uk", self.audio_uk.as_deref()),
+            ("audio_us", self.audio_us.as_deref()),
+        ]
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
 pub struct ImportSummary {
     pub total: u64,
     pub upserted: u64,
     pub skipped: u64,
+    pub failed: u64,
+    pub dry_run: bool,
+    pub merge_policy: MergePolicy,
+    /// Text encoding the file was decoded with, when it was read from raw bytes.
+    pub encoding: Option<String>,
+    /// Rows that were skipped, rejected or changed an existing word.
+    pub rows: Vec<ImportRowReport>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportRowStatus {
+    Updated,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowReport {
+    pub line: u64,
+    pub word: Option<String>,
+    pub status: ImportRowStatus,
+    pub reason: Option<String>,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// How an imported entry is combined with a word that already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    /// Replace every field, clearing the ones the import leaves empty.
+    #[default]
+    Overwrite,
+    /// Leave existing words untouched and only add them to the list.
+    KeepExisting,
+    /// Only fill fields that are currently empty.
+    FillEmpty,
+    /// Add new meanings after the existing ones and fill other empty fields.
+    AppendMeanings,
+}
+
+impl MergePolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            MergePolicy::Overwrite => "overwrite",
+            MergePolicy::KeepExisting => "keep_existing",
+            MergePolicy::FillEmpty => "fill_empty",
+            MergePolicy::AppendMeanings => "append_meanings",
+        }
+    }
+}
+
+/// Where an import comes from, recorded as its provenance.
+pub(crate) struct ImportSource<'a> {
+    /// Importer that produced the entries, e.g. `csv` or `anki`.
+    pub(crate) kind: &'static str,
+    /// Original file or package name, when known.
+    pub(crate) name: Option<&'a str>,
+    pub(crate) merge_policy: MergePolicy,
+}
+
+/// The list and import run that imported entries are attributed to.
+pub(crate) struct ImportTarget {
+    pub(crate) list_id: i64,
+    pub(crate) import_id: i64,
+}
+
+pub(crate) struct UpsertedWord {
+    pub(crate) id: i64,
+    /// Fields of an existing word that the import changed.
+    pub(crate) changes: Vec<FieldChange>,
 }
 
 #[derive(Debug, Serialize)]
@@ -77,6 +217,29 @@ pub struct LearningWord {
     pub audio_uk: Option<String>,
     pub audio_us: Option<String>,
     pub proficiency_score: i64,
+    pub due_at: Option<String>,
+    /// Name of the list whose dictionary supplied the shown definition, set
+    /// only when more than one list defines the word.
+    pub definition_source: Option<String>,
+}
+
+/// One dictionary's definition of a word, with where and when it was imported.
+#[derive(Debug, Serialize)]
+pub struct WordDefinition {
+    pub word_list_id: i64,
+    pub word_list_name: String,
+    pub source_kind: String,
+    pub source_name: Option<String>,
+    pub imported_at: String,
+    pub updated_at: String,
+    pub phonetic: Option<String>,
+    pub part_of_speech_and_meanings: Option<String>,
+    pub example_sentence: Option<String>,
+    pub example_translation: Option<String>,
+    pub audio_uk: Option<String>,
+    pub audio_us: Option<String>,
+    /// Whether this definition is the one stored on the shared word.
+    pub is_displayed: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -84,6 +247,182 @@ pub struct LearningProgress {
     pub word_id: i64,
     pub proficiency_score: i64,
     pub learn_count: i64,
+    pub interval_days: f64,
+    pub due_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoSummary {
+    pub undone: u64,
+    pub word_ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LearningSessionCard {
+    pub position: i64,
+    pub grade: Option<String>,
+    pub answered_at: Option<String>,
+    pub word: LearningWord,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LearningSession {
+    pub id: i64,
+    pub word_list_id: i64,
+    pub cursor: i64,
+    pub status: String,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub cards: Vec<LearningSessionCard>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LearningSessionAdvance {
+    pub session: LearningSession,
+    pub progress: Option<LearningProgress>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionProfile {
+    pub session_size: i64,
+    pub new_word_quota: i64,
+    pub review_quota: i64,
+    pub low_quota: i64,
+    pub mid_quota: i64,
+    pub high_quota: i64,
+    /// Scores below this fall into the low bucket.
+    pub low_score_below: i64,
+    /// Scores from this upwards fall into the high bucket; the rest are mid.
+    pub high_score_from: i64,
+}
+
+impl Default for SessionProfile {
+    fn default() -> Self {
+        Self {
+            session_size: 50,
+            new_word_quota: 20,
+            review_quota: 30,
+            low_quota: 20,
+            mid_quota: 6,
+            high_quota: 4,
+            low_score_below: 4,
+            high_score_from: 9,
+        }
+    }
+}
+
+impl SessionProfile {
+    fn validate(&self) -> Result<()> {
+        if !(1..=MAX_SESSION_SIZE).contains(&self.session_size) {
+            bail!("每轮单词数量需在 1 到 {MAX_SESSION_SIZE} 之间");
+        }
+        let quotas = [
+            self.new_word_quota,
+            self.review_quota,
+            self.low_quota,
+            self.mid_quota,
+            self.high_quota,
+        ];
+        if quotas.iter().any(|quota| *quota < 0) {
+            bail!("分组数量不能为负数");
+        }
+        if !(1..=10).contains(&self.low_score_below)
+            || !(self.low_score_below..=10).contains(&self.high_score_from)
+        {
+            bail!("熟练度分组阈值需满足 1 ≤ 低分上限 ≤ 高分下限 ≤ 10");
+        }
+        Ok(())
+    }
+}
+
+/// What produced a `study_log` row: the know/don't-know buttons or an explicit grade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StudyEventKind {
+    Correct,
+    Incorrect,
+    Grade,
+}
+
+impl StudyEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StudyEventKind::Correct => "correct",
+            StudyEventKind::Incorrect => "incorrect",
+            StudyEventKind::Grade => "grade",
+        }
+    }
+}
+
+/// Answer details only the frontend knows about.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct StudyEventContext {
+    pub response_ms: Option<i64>,
+    pub study_mode: Option<String>,
+}
+
+impl StudyEventContext {
+    fn normalized(self) -> Result<Self> {
+        if self.response_ms.is_some_and(|ms| ms < 0) {
+            bail!("作答用时不能为负数");
+        }
+        let study_mode = self
+            .study_mode
+            .map(|mode| mode.trim().to_string())
+            .filter(|mode| !mode.is_empty());
+        if study_mode
+            .as_ref()
+            .is_some_and(|mode| mode.chars().count() > MAX_STUDY_MODE_CHARS)
+        {
+            bail!("学习模式名称不能超过 {MAX_STUDY_MODE_CHARS} 个字符");
+        }
+        Ok(Self {
+            response_ms: self.response_ms,
+            study_mode,
+        })
+    }
+}
+
+struct StudyEvent {
+    kind: StudyEventKind,
+    session_id: Option<i64>,
+    context: StudyEventContext,
+}
+
+impl StudyEvent {
+    fn new(kind: StudyEventKind, context: StudyEventContext) -> Self {
+        Self {
+            kind,
+            session_id: None,
+            context,
+        }
+    }
+}
+
+/// Maps the UTC timestamps stored in the database onto local study days.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StudyClock {
+    /// Local offset from UTC in minutes, e.g. 480 for UTC+8.
+    pub utc_offset_minutes: i64,
+    /// Local hour a study day starts at; anything earlier counts towards the previous day.
+    pub day_start_hour: i64,
+}
+
+impl StudyClock {
+    fn validate(&self) -> Result<()> {
+        if !(-12 * 60..=14 * 60).contains(&self.utc_offset_minutes) {
+            bail!("时区偏移需在 UTC-12:00 到 UTC+14:00 之间");
+        }
+        if !(0..24).contains(&self.day_start_hour) {
+            bail!("每日开始时间需在 0 到 23 点之间");
+        }
+        Ok(())
+    }
+
+    /// SQLite modifier that turns a UTC timestamp into its study day via `date(ts, modifier)`.
+    pub(crate) fn day_modifier(&self) -> String {
+        format!("{:+} minutes", self.utc_offset_minutes - self.day_start_hour * 60)
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -115,22 +454,43 @@ impl StudyCalendarCache {
     }
 }
 
-fn validate_headers(headers: &csv::StringRecord) -> Result<()> {
-    let normalized: Vec<String> = headers
-        .iter()
-        .map(|header| header.trim_start_matches('\u{feff}').trim().to_string())
-        .collect();
+/// Shared connection pool, opened and migrated once by `init_database`.
+#[derive(Default)]
+pub struct Database {
+    pool: Mutex<Option<SqlitePool>>,
+    path: OnceLock<PathBuf>,
+}
+
+impl Database {
+    pub(crate) fn pool(&self) -> Result<SqlitePool> {
+        self.pool
+            .lock()
+            .ok()
+            .and_then(|pool| pool.clone())
+            .context("数据库尚未初始化")
+    }
 
-    for expected in REQUIRED_HEADERS {
-        if !normalized.iter().any(|header| header == expected) {
-            bail!("CSV 缺少必需列: {expected}");
+    pub(crate) fn set(&self, pool: SqlitePool) {
+        if let Ok(mut current) = self.pool.lock() {
+            *current = Some(pool);
         }
     }
 
-    Ok(())
+    pub(crate) fn take(&self) -> Option<SqlitePool> {
+        self.pool.lock().ok().and_then(|mut pool| pool.take())
+    }
+
+    pub(crate) fn set_path(&self, path: PathBuf) {
+        let _ = self.path.set(path);
+    }
+
+    /// Location of the database file; `None` for in-memory databases.
+    pub(crate) fn path(&self) -> Option<&Path> {
+        self.path.get().map(PathBuf::as_path)
+    }
 }
 
-async fn open_pool(app: &tauri::AppHandle) -> Result<SqlitePool> {
+fn database_path(app: &tauri::AppHandle) -> Result<PathBuf> {
     let app_data_dir = app
         .path()
         .app_data_dir()
@@ -139,9 +499,12 @@ async fn open_pool(app: &tauri::AppHandle) -> Result<SqlitePool> {
         format!("创建应用数据目录失败: {}", app_data_dir.display())
     })?;
 
-    let db_path = app_data_dir.join("word-card.sqlite3");
+    Ok(app_data_dir.join("word-card.sqlite3"))
+}
+
+pub(crate) async fn open_pool(db_path: &Path) -> Result<SqlitePool> {
     let options = SqliteConnectOptions::new()
-        .filename(&db_path)
+        .filename(db_path)
         .create_if_missing(true)
         .busy_timeout(Duration::from_secs(3));
 
@@ -159,208 +522,11 @@ async fn open_pool(app: &tauri::AppHandle) -> Result<SqlitePool> {
     Ok(pool)
 }
 
-async fn ensure_schema(pool: &SqlitePool) -> Result<()> {
-    sqlx::query(
-        r#"
-CREATE TABLE IF NOT EXISTS word_list (
-  id INTEGER PRIMARY KEY AUTOINCREMENT,
-  name TEXT NOT NULL UNIQUE,
-  created_at TEXT NOT NULL DEFAULT (datetime('now'))
-)
-"#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize word_list table")?;
-
-    sqlx::query(
-        r#"
-CREATE TABLE IF NOT EXISTS word (
-  id INTEGER PRIMARY KEY AUTOINCREMENT,
-  word TEXT NOT NULL UNIQUE,
-  phonetic TEXT,
-  part_of_speech_and_meanings TEXT,
-  example_sentence TEXT,
-  example_translation TEXT,
-  audio_uk TEXT,
-  audio_us TEXT,
-  created_at TEXT NOT NULL DEFAULT (datetime('now'))
-)
-"#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize word table")?;
-
-    sqlx::query(
-        r#"
-CREATE TABLE IF NOT EXISTS word_list_map (
-  word_list_id INTEGER NOT NULL,
-  word_id INTEGER NOT NULL,
-  PRIMARY KEY (word_list_id, word_id),
-  FOREIGN KEY (word_list_id) REFERENCES word_list(id),
-  FOREIGN KEY (word_id) REFERENCES word(id)
-)
-"#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize word_list_map table")?;
-
-    sqlx::query(
-        r#"
-CREATE TABLE IF NOT EXISTS word_list_state (
-  id INTEGER PRIMARY KEY CHECK (id = 1),
-  active_word_list_id INTEGER,
-  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-)
-"#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize word_list_state table")?;
-
-    sqlx::query(
-        "INSERT OR IGNORE INTO word_list_state (id, active_word_list_id) VALUES (1, NULL)",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize word_list_state row")?;
-
-    sqlx::query(
-        r#"
-CREATE TABLE IF NOT EXISTS user_word_learning (
-  word_id INTEGER PRIMARY KEY,
-  proficiency_score INTEGER NOT NULL DEFAULT 0,
-  last_learned_at TEXT,
-  learn_count INTEGER NOT NULL DEFAULT 0,
-  is_fuzzy INTEGER NOT NULL DEFAULT 0,
-  fuzzy_marked_at TEXT,
-  FOREIGN KEY (word_id) REFERENCES word(id)
-)
-"#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize user_word_learning table")?;
-
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_user_word_learning_score ON user_word_learning(proficiency_score)",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize user_word_learning indexes")?;
-
-    sqlx::query(
-        r#"
-CREATE TABLE IF NOT EXISTS study_log (
-  id INTEGER PRIMARY KEY AUTOINCREMENT,
-  word_id INTEGER NOT NULL,
-  learned_at TEXT NOT NULL DEFAULT (datetime('now')),
-  FOREIGN KEY (word_id) REFERENCES word(id)
-)
-"#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize study_log table")?;
-
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_study_log_learned_at ON study_log(learned_at)",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to initialize study_log index")?;
-
-    ensure_learning_columns(pool).await?;
-
-    Ok(())
-}
-
-async fn ensure_learning_columns(pool: &SqlitePool) -> Result<()> {
-    let rows = sqlx::query("PRAGMA table_info(user_word_learning)")
-        .fetch_all(pool)
-        .await
-        .context("Failed to read user_word_learning schema")?;
-
-    let mut columns = HashSet::new();
-    for row in rows {
-        let name: String = row
-            .try_get("name")
-            .context("Failed to read user_word_learning column name")?;
-        columns.insert(name);
-    }
-
-    let has_last_studied = columns.contains("last_studied_at");
-    let has_study_count = columns.contains("study_count");
-    let has_is_fuzzy = columns.contains("is_fuzzy");
-    let has_fuzzy_marked_at = columns.contains("fuzzy_marked_at");
-
-    if !columns.contains("last_learned_at") {
-        sqlx::query("ALTER TABLE user_word_learning ADD COLUMN last_learned_at TEXT")
-            .execute(pool)
-            .await
-            .context("Failed to add last_learned_at column")?;
-    }
-
-    if !columns.contains("learn_count") {
-        sqlx::query(
-            "ALTER TABLE user_word_learning ADD COLUMN learn_count INTEGER NOT NULL DEFAULT 0",
-        )
-        .execute(pool)
-        .await
-        .context("Failed to add learn_count column")?;
-    }
-
-    if !has_is_fuzzy {
-        sqlx::query(
-            "ALTER TABLE user_word_learning ADD COLUMN is_fuzzy INTEGER NOT NULL DEFAULT 0",
-        )
-        .execute(pool)
-        .await
-        .context("Failed to add is_fuzzy column")?;
-    }
-
-    if !has_fuzzy_marked_at {
-        sqlx::query("ALTER TABLE user_word_learning ADD COLUMN fuzzy_marked_at TEXT")
-            .execute(pool)
-            .await
-            .context("Failed to add fuzzy_marked_at column")?;
-    }
-
-    if has_last_studied {
-        sqlx::query(
-            r#"
-UPDATE user_word_learning
-SET last_learned_at = COALESCE(last_learned_at, last_studied_at)
-WHERE last_studied_at IS NOT NULL
-"#,
-        )
-        .execute(pool)
-        .await
-        .context("Failed to migrate last_studied_at")?;
-    }
-
-    if has_study_count {
-        sqlx::query(
-            r#"
-UPDATE user_word_learning
-SET learn_count = CASE
-  WHEN learn_count IS NULL OR learn_count = 0 THEN study_count
-  ELSE learn_count
-END
-WHERE study_count IS NOT NULL
-"#,
-        )
-        .execute(pool)
-        .await
-        .context("Failed to migrate study_count")?;
-    }
-
-    Ok(())
+pub(crate) async fn ensure_schema(pool: &SqlitePool) -> Result<()> {
+    migrations::migrate(pool).await
 }
 
-async fn sync_fuzzy_word_list(pool: &SqlitePool) -> Result<()> {
+pub(crate) async fn sync_fuzzy_word_list(pool: &SqlitePool) -> Result<()> {
     let fuzzy_count: i64 = sqlx::query_scalar(
         "SELECT COUNT(1) FROM user_word_learning WHERE is_fuzzy = 1",
     )
@@ -377,6 +543,7 @@ async fn sync_fuzzy_word_list(pool: &SqlitePool) -> Result<()> {
 
     if fuzzy_count == 0 {
         if let Some(list_id) = list_id {
+            delete_learning_sessions_for_list(&mut tx, list_id).await?;
             sqlx::query("DELETE FROM word_list_map WHERE word_list_id = ?")
                 .bind(list_id)
                 .execute(&mut *tx)
@@ -438,22 +605,22 @@ SELECT ?, word_id FROM user_word_learning WHERE is_fuzzy = 1
     Ok(())
 }
 
-async fn mark_word_fuzzy(pool: &SqlitePool, word_id: i64) -> Result<()> {
+async fn mark_word_fuzzy(conn: &mut SqliteConnection, word_id: i64) -> Result<()> {
     let result = sqlx::query(
         "UPDATE user_word_learning SET is_fuzzy = 1, fuzzy_marked_at = datetime('now') WHERE word_id = ?",
     )
     .bind(word_id)
-    .execute(pool)
+    .execute(&mut *conn)
     .await
     .context("更新模糊标记失败")?;
 
     if result.rows_affected() == 0 {
-        ensure_learning_row(pool, word_id).await?;
+        ensure_learning_row(conn, word_id).await?;
         sqlx::query(
             "UPDATE user_word_learning SET is_fuzzy = 1, fuzzy_marked_at = datetime('now') WHERE word_id = ?",
         )
         .bind(word_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .context("更新模糊标记失败")?;
     }
@@ -461,17 +628,45 @@ async fn mark_word_fuzzy(pool: &SqlitePool, word_id: i64) -> Result<()> {
     Ok(())
 }
 
-pub async fn init_database(app: &tauri::AppHandle) -> Result<()> {
-    let pool = open_pool(app).await?;
+async fn delete_learning_sessions_for_list(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    word_list_id: i64,
+) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM learning_session_item WHERE session_id IN (SELECT id FROM learning_session WHERE word_list_id = ?)",
+    )
+    .bind(word_list_id)
+    .execute(&mut **tx)
+    .await
+    .context("清理学习会话单词失败")?;
+    sqlx::query("DELETE FROM learning_session WHERE word_list_id = ?")
+        .bind(word_list_id)
+        .execute(&mut **tx)
+        .await
+        .context("清理学习会话失败")?;
+    Ok(())
+}
+
+pub async fn init_database(app: &tauri::AppHandle, db: &Database) -> Result<()> {
+    let db_path = database_path(app)?;
+    db.set_path(db_path.clone());
+    let pool = open_pool(&db_path).await?;
     ensure_schema(&pool).await?;
     sync_fuzzy_word_list(&pool).await?;
+    db.set(pool);
     Ok(())
 }
 
-pub async fn list_word_lists(app: &tauri::AppHandle) -> Result<Vec<WordListCard>> {
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
+pub async fn list_word_lists(db: &Database) -> Result<Vec<WordListCard>> {
+    let pool = db.pool()?;
     sync_fuzzy_word_list(&pool).await?;
+    list_word_lists_with_pool(&pool).await
+}
+
+async fn list_word_lists_with_pool(pool: &SqlitePool) -> Result<Vec<WordListCard>> {
+    // Mastery follows the same threshold as the high bucket of the session profile.
+    let mastered_score = read_session_profile(pool).await?.high_score_from;
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
 
     let rows = sqlx::query(
         r#"
@@ -479,18 +674,25 @@ SELECT
   wl.id AS id,
   wl.name AS name,
   COUNT(wlm.word_id) AS word_count,
+  COUNT(uwl.word_id) AS learned_count,
+  COUNT(CASE WHEN uwl.proficiency_score >= ? THEN 1 END) AS mastered_count,
+  COUNT(CASE WHEN date(uwl.due_at, ?) <= date('now', ?) THEN 1 END) AS due_count,
   CASE
     WHEN wls.active_word_list_id = wl.id THEN 1
     ELSE 0
   END AS is_active
 FROM word_list wl
 LEFT JOIN word_list_map wlm ON wl.id = wlm.word_list_id
+LEFT JOIN user_word_learning uwl ON wlm.word_id = uwl.word_id
 LEFT JOIN word_list_state wls ON wls.id = 1
 GROUP BY wl.id
 ORDER BY is_active DESC, wl.created_at DESC, wl.id DESC
 "#,
     )
-    .fetch_all(&pool)
+    .bind(mastered_score)
+    .bind(&day_modifier)
+    .bind(&day_modifier)
+    .fetch_all(pool)
     .await
     .context("读取词库列表失败")?;
 
@@ -501,6 +703,15 @@ ORDER BY is_active DESC, wl.created_at DESC, wl.id DESC
         let word_count: i64 = row
             .try_get("word_count")
             .context("读取词库单词数量失败")?;
+        let learned_count: i64 = row
+            .try_get("learned_count")
+            .context("读取词库已学数量失败")?;
+        let mastered_count: i64 = row
+            .try_get("mastered_count")
+            .context("读取词库已掌握数量失败")?;
+        let due_count: i64 = row
+            .try_get("due_count")
+            .context("读取词库待复习数量失败")?;
         let is_active: i64 = row
             .try_get("is_active")
             .context("读取词库激活状态失败")?;
@@ -509,6 +720,9 @@ ORDER BY is_active DESC, wl.created_at DESC, wl.id DESC
             id,
             name,
             word_count,
+            learned_count,
+            mastered_count,
+            due_count,
             is_active: is_active != 0,
             is_system,
         });
@@ -516,7 +730,7 @@ ORDER BY is_active DESC, wl.created_at DESC, wl.id DESC
     Ok(lists)
 }
 
-pub async fn create_word_list(app: &tauri::AppHandle, name: &str) -> Result<i64> {
+pub async fn create_word_list(db: &Database, name: &str) -> Result<i64> {
     let trimmed = name.trim();
     if trimmed.is_empty() {
         bail!("词库名称不能为空");
@@ -525,8 +739,7 @@ pub async fn create_word_list(app: &tauri::AppHandle, name: &str) -> Result<i64>
         bail!("系统词库不可创建");
     }
 
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
+    let pool = db.pool()?;
 
     let existing: Option<i64> = sqlx::query_scalar("SELECT id FROM word_list WHERE name = ?")
         .bind(trimmed)
@@ -545,13 +758,12 @@ pub async fn create_word_list(app: &tauri::AppHandle, name: &str) -> Result<i64>
     Ok(result.last_insert_rowid())
 }
 
-pub async fn set_active_word_list(app: &tauri::AppHandle, word_list_id: i64) -> Result<()> {
+pub async fn set_active_word_list(db: &Database, word_list_id: i64) -> Result<()> {
     if word_list_id <= 0 {
         bail!("词库不存在");
     }
 
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
+    let pool = db.pool()?;
 
     let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM word_list WHERE id = ?")
         .bind(word_list_id)
@@ -572,9 +784,8 @@ pub async fn set_active_word_list(app: &tauri::AppHandle, word_list_id: i64) ->
     Ok(())
 }
 
-pub async fn clear_active_word_list(app: &tauri::AppHandle) -> Result<()> {
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
+pub async fn clear_active_word_list(db: &Database) -> Result<()> {
+    let pool = db.pool()?;
 
     sqlx::query(
         "UPDATE word_list_state SET active_word_list_id = NULL, updated_at = datetime('now') WHERE id = 1",
@@ -585,18 +796,15 @@ pub async fn clear_active_word_list(app: &tauri::AppHandle) -> Result<()> {
     Ok(())
 }
 
-pub async fn delete_word_list(app: &tauri::AppHandle, word_list_id: i64) -> Result<()> {
+pub async fn delete_word_list(db: &Database, word_list_id: i64) -> Result<()> {
     if word_list_id <= 0 {
         bail!("词库不存在");
     }
 
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
-
-    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+    let pool = db.pool()?;
     let row = sqlx::query("SELECT id, name FROM word_list WHERE id = ?")
         .bind(word_list_id)
-        .fetch_optional(&mut *tx)
+        .fetch_optional(&pool)
         .await
         .context("检查词库是否存在失败")?;
     let Some(row) = row else {
@@ -608,6 +816,10 @@ pub async fn delete_word_list(app: &tauri::AppHandle, word_list_id: i64) -> Resu
         bail!("系统词库不可删除");
     }
 
+    backup::create_automatic_backup(db, "delete-word-list").await?;
+
+    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+
     let orphaned_ids: Vec<i64> = sqlx::query_scalar(
         r#"
 SELECT wlm.word_id
@@ -627,12 +839,27 @@ WHERE wlm.word_list_id = ?
     .await
     .context("读取孤立单词失败")?;
 
+    let defined_ids: Vec<i64> =
+        sqlx::query_scalar("SELECT word_id FROM word_definition WHERE word_list_id = ?")
+            .bind(word_list_id)
+            .fetch_all(&mut *tx)
+            .await
+            .context("读取词库释义失败")?;
+
+    delete_learning_sessions_for_list(&mut tx, word_list_id).await?;
+
     sqlx::query("DELETE FROM word_list_map WHERE word_list_id = ?")
         .bind(word_list_id)
         .execute(&mut *tx)
         .await
         .context("删除词库关联失败")?;
 
+    sqlx::query("DELETE FROM word_definition WHERE word_list_id = ?")
+        .bind(word_list_id)
+        .execute(&mut *tx)
+        .await
+        .context("删除词库释义失败")?;
+
     if !orphaned_ids.is_empty() {
         let mut builder = QueryBuilder::new(
             "DELETE FROM user_word_learning WHERE word_id IN (",
@@ -648,7 +875,7 @@ WHERE wlm.word_list_id = ?
             .await
             .context("清理学习记录失败")?;
 
-        let mut builder = QueryBuilder::new("DELETE FROM study_log WHERE word_id IN (");
+        let mut builder = QueryBuilder::new("DELETE FROM grade_history WHERE word_id IN (");
         let mut separated = builder.separated(", ");
         for id in &orphaned_ids {
             separated.push_bind(id);
@@ -658,7 +885,19 @@ WHERE wlm.word_list_id = ?
             .build()
             .execute(&mut *tx)
             .await
-            .context("清理学习记录日志失败")?;
+            .context("清理评分历史失败")?;
+
+        let mut builder = QueryBuilder::new("DELETE FROM study_log WHERE word_id IN (");
+        let mut separated = builder.separated(", ");
+        for id in &orphaned_ids {
+            separated.push_bind(id);
+        }
+        builder.push(")");
+        builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .context("清理学习记录日志失败")?;
 
         let mut builder = QueryBuilder::new("DELETE FROM word WHERE id IN (");
         let mut separated = builder.separated(", ");
@@ -673,6 +912,53 @@ WHERE wlm.word_list_id = ?
             .context("清理孤立单词失败")?;
     }
 
+    // Shared words may still show this list's definition; fall back to the
+    // most recent definition another list supplied.
+    if !defined_ids.is_empty() {
+        let mut builder = QueryBuilder::new(
+            r#"
+UPDATE word
+SET (
+  phonetic,
+  part_of_speech_and_meanings,
+  example_sentence,
+  example_translation,
+  audio_uk,
+  audio_us
+) = (
+  SELECT
+    wd.phonetic,
+    wd.part_of_speech_and_meanings,
+    wd.example_sentence,
+    wd.example_translation,
+    wd.audio_uk,
+    wd.audio_us
+  FROM word_definition wd
+  WHERE wd.word_id = word.id
+  ORDER BY wd.updated_at DESC, wd.import_id DESC
+  LIMIT 1
+)
+WHERE EXISTS (SELECT 1 FROM word_definition wd WHERE wd.word_id = word.id)
+  AND id IN ("#,
+        );
+        let mut separated = builder.separated(", ");
+        for id in &defined_ids {
+            separated.push_bind(id);
+        }
+        builder.push(")");
+        builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .context("恢复共享单词释义失败")?;
+    }
+
+    sqlx::query("DELETE FROM dictionary_import WHERE word_list_id = ?")
+        .bind(word_list_id)
+        .execute(&mut *tx)
+        .await
+        .context("删除词库导入记录失败")?;
+
     sqlx::query("DELETE FROM word_list WHERE id = ?")
         .bind(word_list_id)
         .execute(&mut *tx)
@@ -723,12 +1009,14 @@ async fn ensure_word_list_id(
     Ok(result.last_insert_rowid())
 }
 
-async fn import_dictionary_csv_with_pool(
-    pool: &SqlitePool,
+/// Validates the target list name, clears its words when overwriting and
+/// records the import run that the entries will be attributed to.
+pub(crate) async fn prepare_import_list(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     name: &str,
-    csv_content: &str,
     overwrite: bool,
-) -> Result<ImportSummary> {
+    source: &ImportSource<'_>,
+) -> Result<ImportTarget> {
     let trimmed_name = name.trim();
     if trimmed_name.is_empty() {
         bail!("词库名称不能为空");
@@ -737,46 +1025,225 @@ async fn import_dictionary_csv_with_pool(
         bail!("系统词库不可导入");
     }
 
-    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
-    let list_id = ensure_word_list_id(&mut tx, trimmed_name).await?;
+    let list_id = ensure_word_list_id(tx, trimmed_name).await?;
 
     if overwrite {
         sqlx::query("DELETE FROM word_list_map WHERE word_list_id = ?")
             .bind(list_id)
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await
             .context("清理词库关联失败")?;
+        sqlx::query("DELETE FROM word_definition WHERE word_list_id = ?")
+            .bind(list_id)
+            .execute(&mut **tx)
+            .await
+            .context("清理词库释义失败")?;
     }
 
-    let csv_content = csv_content.trim_start_matches('\u{feff}');
-    let mut reader = csv::ReaderBuilder::new()
-        .from_reader(csv_content.as_bytes());
-    let headers = reader.headers().context("读取 CSV 表头失败")?.clone();
-    validate_headers(&headers)?;
+    let result = sqlx::query(
+        "INSERT INTO dictionary_import (word_list_id, source_kind, source_name, merge_policy) VALUES (?, ?, ?, ?)",
+    )
+    .bind(list_id)
+    .bind(source.kind)
+    .bind(source.name)
+    .bind(source.merge_policy.as_str())
+    .execute(&mut **tx)
+    .await
+    .context("记录词库导入来源失败")?;
 
-    let mut total = 0_u64;
-    let mut upserted = 0_u64;
-    let mut skipped = 0_u64;
+    Ok(ImportTarget {
+        list_id,
+        import_id: result.last_insert_rowid(),
+    })
+}
 
-    for row in reader.deserialize::<DictionaryCsvRecord>() {
-        total += 1;
-        let record = row.context("解析 CSV 行失败")?;
-        let word = record.word.trim();
-        if word.is_empty() {
-            skipped += 1;
-            continue;
+/// Loads the current entries for a set of headwords, keyed by word.
+async fn find_dictionary_records(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    words: &[&str],
+) -> Result<HashMap<String, DictionaryRecord>> {
+    let mut builder = QueryBuilder::new(
+        r#"
+SELECT
+  word,
+  phonetic,
+  part_of_speech_and_meanings,
+  example_sentence,
+  example_translation,
+  audio_uk,
+  audio_us
+FROM word
+WHERE word IN ("#,
+    );
+    let mut separated = builder.separated(", ");
+    for word in words {
+        separated.push_bind(*word);
+    }
+    builder.push(")");
+    let rows = builder
+        .build()
+        .fetch_all(&mut **tx)
+        .await
+        .context("读取已有单词失败")?;
+
+    let mut records = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let record = DictionaryRecord {
+            word: row.try_get("word").context("读取已有单词失败")?,
+            phonetic: row.try_get("phonetic").context("读取已有单词失败")?,
+            part_of_speech_and_meanings: row
+                .try_get("part_of_speech_and_meanings")
+                .context("读取已有单词失败")?,
+            example_sentence: row.try_get("example_sentence").context("读取已有单词失败")?,
+            example_translation: row
+                .try_get("example_translation")
+                .context("读取已有单词失败")?,
+            audio_uk: row.try_get("audio_uk").context("读取已有单词失败")?,
+            audio_us: row.try_get("audio_us").context("读取已有单词失败")?,
         }
+        .normalized();
+        records.insert(record.word.clone(), record);
+    }
+    Ok(records)
+}
 
-        let phonetic = normalize_optional(record.phonetic);
-        let meanings = normalize_optional(record.part_of_speech_and_meanings);
-        let example_sentence = normalize_optional(record.example_sentence);
-        let example_translation = normalize_optional(record.example_translation);
-        let audio_uk = normalize_optional(record.audio_uk);
-        let audio_us = normalize_optional(record.audio_us);
-        let word_value = word.to_string();
+fn append_meanings(existing: Option<String>, incoming: Option<String>) -> Option<String> {
+    match (existing, incoming) {
+        (Some(existing), Some(incoming)) if !existing.contains(&incoming) => {
+            Some(format!("{existing}\n{incoming}"))
+        }
+        (existing, incoming) => existing.or(incoming),
+    }
+}
 
-        sqlx::query(
-            r#"
+fn merge_record(
+    existing: &DictionaryRecord,
+    incoming: DictionaryRecord,
+    policy: MergePolicy,
+) -> DictionaryRecord {
+    let fill = |current: &Option<String>, new: Option<String>| current.clone().or(new);
+    match policy {
+        MergePolicy::Overwrite => incoming,
+        MergePolicy::KeepExisting => DictionaryRecord {
+            word: incoming.word,
+            phonetic: existing.phonetic.clone(),
+            part_of_speech_and_meanings: existing.part_of_speech_and_meanings.clone(),
+            example_sentence: existing.example_sentence.clone(),
+            example_translation: existing.example_translation.clone(),
+            audio_uk: existing.audio_uk.clone(),
+            audio_us: existing.audio_us.clone(),
+        },
+        MergePolicy::FillEmpty | MergePolicy::AppendMeanings => DictionaryRecord {
+            word: incoming.word,
+            phonetic: fill(&existing.phonetic, incoming.phonetic),
+            part_of_speech_and_meanings: if policy == MergePolicy::AppendMeanings {
+                append_meanings(
+                    existing.part_of_speech_and_meanings.clone(),
+                    incoming.part_of_speech_and_meanings,
+                )
+            } else {
+                fill(
+                    &existing.part_of_speech_and_meanings,
+                    incoming.part_of_speech_and_meanings,
+                )
+            },
+            example_sentence: fill(&existing.example_sentence, incoming.example_sentence),
+            example_translation: fill(
+                &existing.example_translation,
+                incoming.example_translation,
+            ),
+            audio_uk: fill(&existing.audio_uk, incoming.audio_uk),
+            audio_us: fill(&existing.audio_us, incoming.audio_us),
+        },
+    }
+}
+
+fn field_changes(existing: &DictionaryRecord, merged: &DictionaryRecord) -> Vec<FieldChange> {
+    existing
+        .fields()
+        .into_iter()
+        .zip(merged.fields())
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange {
+            field,
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        })
+        .collect()
+}
+
+/// Upserts one dictionary entry under the merge policy and links it to the list.
+/// Returns `None` when the entry has no word and was skipped.
+pub(crate) async fn upsert_dictionary_record(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    target: &ImportTarget,
+    record: DictionaryRecord,
+    policy: MergePolicy,
+) -> Result<Option<UpsertedWord>> {
+    let mut upserted = upsert_dictionary_records(tx, target, vec![record], policy).await?;
+    Ok(upserted.pop().flatten())
+}
+
+/// Upserts dictionary entries in order under the merge policy and links them to
+/// the list. The entries as supplied are also kept as the list's own
+/// definitions. Returns one result per entry, `None` for entries without a word.
+pub(crate) async fn upsert_dictionary_records(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    target: &ImportTarget,
+    records: Vec<DictionaryRecord>,
+    policy: MergePolicy,
+) -> Result<Vec<Option<UpsertedWord>>> {
+    let records: Vec<DictionaryRecord> =
+        records.into_iter().map(DictionaryRecord::normalized).collect();
+    let mut upserted = Vec::with_capacity(records.len());
+    for chunk in records.chunks(UPSERT_CHUNK_ROWS) {
+        upserted.extend(upsert_dictionary_chunk(tx, target, chunk, policy).await?);
+    }
+    Ok(upserted)
+}
+
+async fn upsert_dictionary_chunk(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    target: &ImportTarget,
+    records: &[DictionaryRecord],
+    policy: MergePolicy,
+) -> Result<Vec<Option<UpsertedWord>>> {
+    let words: Vec<&str> = records
+        .iter()
+        .map(|record| record.word.as_str())
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return Ok(records.iter().map(|_| None).collect());
+    }
+
+    // Merge in file order so a word repeated within the chunk sees its earlier row.
+    let mut current = find_dictionary_records(tx, &words).await?;
+    let mut definitions: HashMap<&str, &DictionaryRecord> = HashMap::new();
+    let mut unique_words: Vec<&str> = Vec::new();
+    let mut changes = Vec::with_capacity(records.len());
+    for record in records {
+        if record.word.is_empty() {
+            changes.push(None);
+            continue;
+        }
+        let (merged, record_changes) = match current.get(&record.word) {
+            Some(existing) => {
+                let merged = merge_record(existing, record.clone(), policy);
+                let record_changes = field_changes(existing, &merged);
+                (merged, record_changes)
+            }
+            None => (record.clone(), Vec::new()),
+        };
+        if definitions.insert(&record.word, record).is_none() {
+            unique_words.push(&record.word);
+        }
+        current.insert(record.word.clone(), merged);
+        changes.push(Some(record_changes));
+    }
+
+    let mut builder = QueryBuilder::new(
+        r#"
 INSERT INTO word (
   word,
   phonetic,
@@ -786,7 +1253,21 @@ INSERT INTO word (
   audio_uk,
   audio_us
 )
-VALUES (?, ?, ?, ?, ?, ?, ?)
+"#,
+    );
+    builder.push_values(&unique_words, |mut values, word| {
+        let merged = &current[*word];
+        values
+            .push_bind(*word)
+            .push_bind(merged.phonetic.clone())
+            .push_bind(merged.part_of_speech_and_meanings.clone())
+            .push_bind(merged.example_sentence.clone())
+            .push_bind(merged.example_translation.clone())
+            .push_bind(merged.audio_uk.clone())
+            .push_bind(merged.audio_us.clone());
+    });
+    builder.push(
+        r#"
 ON CONFLICT(word) DO UPDATE SET
   phonetic = excluded.phonetic,
   part_of_speech_and_meanings = excluded.part_of_speech_and_meanings,
@@ -795,51 +1276,267 @@ ON CONFLICT(word) DO UPDATE SET
   audio_uk = excluded.audio_uk,
   audio_us = excluded.audio_us
 "#,
-        )
-        .bind(&word_value)
-        .bind(phonetic)
-        .bind(meanings)
-        .bind(example_sentence)
-        .bind(example_translation)
-        .bind(audio_uk)
-        .bind(audio_us)
-        .execute(&mut *tx)
+    );
+    builder
+        .build()
+        .execute(&mut **tx)
         .await
         .context("写入单词失败")?;
 
-        let word_id: i64 = sqlx::query_scalar("SELECT id FROM word WHERE word = ?")
-            .bind(&word_value)
-            .fetch_one(&mut *tx)
-            .await
-            .context("读取单词 ID 失败")?;
+    let mut builder = QueryBuilder::new("SELECT id, word FROM word WHERE word IN (");
+    let mut separated = builder.separated(", ");
+    for word in &unique_words {
+        separated.push_bind(*word);
+    }
+    builder.push(")");
+    let rows = builder
+        .build()
+        .fetch_all(&mut **tx)
+        .await
+        .context("读取单词 ID 失败")?;
+    let mut ids = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let id: i64 = row.try_get("id").context("读取单词 ID 失败")?;
+        let word: String = row.try_get("word").context("读取单词 ID 失败")?;
+        ids.insert(word, id);
+    }
+    let id_of = |word: &str| {
+        ids.get(word)
+            .copied()
+            .with_context(|| format!("读取单词 ID 失败: {word}"))
+    };
+    let word_ids = unique_words
+        .iter()
+        .map(|word| id_of(word))
+        .collect::<Result<Vec<i64>>>()?;
+
+    let mut builder =
+        QueryBuilder::new("INSERT OR IGNORE INTO word_list_map (word_list_id, word_id) ");
+    builder.push_values(&word_ids, |mut values, word_id| {
+        values.push_bind(target.list_id).push_bind(*word_id);
+    });
+    builder
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("写入词库关联失败")?;
 
-        sqlx::query("INSERT OR IGNORE INTO word_list_map (word_list_id, word_id) VALUES (?, ?)")
-            .bind(list_id)
-            .bind(word_id)
-            .execute(&mut *tx)
-            .await
-            .context("写入词库关联失败")?;
+    let mut builder = QueryBuilder::new(
+        r#"
+INSERT INTO word_definition (
+  word_id,
+  word_list_id,
+  import_id,
+  phonetic,
+  part_of_speech_and_meanings,
+  example_sentence,
+  example_translation,
+  audio_uk,
+  audio_us
+)
+"#,
+    );
+    builder.push_values(
+        unique_words.iter().zip(&word_ids),
+        |mut values, (word, word_id)| {
+            let definition = definitions[*word];
+            values
+                .push_bind(*word_id)
+                .push_bind(target.list_id)
+                .push_bind(target.import_id)
+                .push_bind(definition.phonetic.clone())
+                .push_bind(definition.part_of_speech_and_meanings.clone())
+                .push_bind(definition.example_sentence.clone())
+                .push_bind(definition.example_translation.clone())
+                .push_bind(definition.audio_uk.clone())
+                .push_bind(definition.audio_us.clone());
+        },
+    );
+    builder.push(
+        r#"
+ON CONFLICT(word_id, word_list_id) DO UPDATE SET
+  import_id = excluded.import_id,
+  phonetic = excluded.phonetic,
+  part_of_speech_and_meanings = excluded.part_of_speech_and_meanings,
+  example_sentence = excluded.example_sentence,
+  example_translation = excluded.example_translation,
+  audio_uk = excluded.audio_uk,
+  audio_us = excluded.audio_us,
+  updated_at = datetime('now')
+"#,
+    );
+    builder
+        .build()
+        .execute(&mut **tx)
+        .await
+        .context("写入词库释义失败")?;
+
+    records
+        .iter()
+        .zip(changes)
+        .map(|(record, changes)| {
+            let Some(changes) = changes else {
+                return Ok(None);
+            };
+            Ok(Some(UpsertedWord {
+                id: id_of(&record.word)?,
+                changes,
+            }))
+        })
+        .collect()
+}
 
-        upserted += 1;
+async fn export_word_list_csv_with_pool(
+    pool: &SqlitePool,
+    word_list_id: i64,
+    include_progress: bool,
+) -> Result<String> {
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM word_list WHERE id = ?")
+        .bind(word_list_id)
+        .fetch_optional(pool)
+        .await
+        .context("检查词库是否存在失败")?;
+    if exists.is_none() {
+        bail!("词库不存在");
     }
 
-    tx.commit().await.context("提交数据库事务失败")?;
-    Ok(ImportSummary {
-        total,
-        upserted,
-        skipped,
-    })
+    let query = format!(
+        r#"
+SELECT
+  w.word,
+  {DEFINITION_COLUMNS},
+  COALESCE(uwl.proficiency_score, 0) AS proficiency_score,
+  COALESCE(uwl.learn_count, 0) AS learn_count,
+  COALESCE(uwl.is_fuzzy, 0) AS is_fuzzy,
+  uwl.last_learned_at
+FROM word_list_map wlm
+JOIN word w ON w.id = wlm.word_id
+LEFT JOIN word_definition wd ON wd.word_id = w.id AND wd.word_list_id = wlm.word_list_id
+LEFT JOIN user_word_learning uwl ON uwl.word_id = w.id
+WHERE wlm.word_list_id = ?
+ORDER BY w.id
+"#
+    );
+    let rows = sqlx::query(&query)
+        .bind(word_list_id)
+        .fetch_all(pool)
+        .await
+        .context("读取词库单词失败")?;
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let mut headers: Vec<&str> = REQUIRED_HEADERS.to_vec();
+    if include_progress {
+        headers.extend(PROGRESS_HEADERS);
+    }
+    writer.write_record(&headers).context("写入 CSV 表头失败")?;
+
+    for row in rows {
+        let mut record: Vec<String> = Vec::with_capacity(headers.len());
+        for column in REQUIRED_HEADERS {
+            let value: Option<String> = row
+                .try_get(column)
+                .with_context(|| format!("读取单词字段失败: {column}"))?;
+            record.push(value.unwrap_or_default());
+        }
+        if include_progress {
+            let score: i64 = row
+                .try_get("proficiency_score")
+                .context("读取熟练度失败")?;
+            let learn_count: i64 = row.try_get("learn_count").context("读取学习次数失败")?;
+            let is_fuzzy: i64 = row.try_get("is_fuzzy").context("读取模糊标记失败")?;
+            let last_learned_at: Option<String> = row
+                .try_get("last_learned_at")
+                .context("读取最近学习时间失败")?;
+            record.push(score.to_string());
+            record.push(learn_count.to_string());
+            record.push(is_fuzzy.to_string());
+            record.push(last_learned_at.unwrap_or_default());
+        }
+        writer.write_record(&record).context("写入 CSV 行失败")?;
+    }
+
+    let bytes = writer.into_inner().context("生成 CSV 失败")?;
+    let content = String::from_utf8(bytes).context("生成 CSV 失败")?;
+    // Spreadsheet apps need the BOM to detect UTF-8; the importer strips it again.
+    Ok(format!("\u{feff}{content}"))
 }
 
-pub async fn import_dictionary_csv(
-    app: &tauri::AppHandle,
-    name: &str,
-    csv_content: &str,
-    overwrite: bool,
-) -> Result<ImportSummary> {
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
-    import_dictionary_csv_with_pool(&pool, name, csv_content, overwrite).await
+pub async fn export_word_list_csv(
+    db: &Database,
+    word_list_id: i64,
+    include_progress: bool,
+) -> Result<String> {
+    let pool = db.pool()?;
+    export_word_list_csv_with_pool(&pool, word_list_id, include_progress).await
+}
+
+async fn list_word_definitions_with_pool(
+    pool: &SqlitePool,
+    word_id: i64,
+) -> Result<Vec<WordDefinition>> {
+    let rows = sqlx::query(
+        r#"
+SELECT
+  wd.word_list_id AS word_list_id,
+  wl.name AS word_list_name,
+  di.source_kind AS source_kind,
+  di.source_name AS source_name,
+  di.imported_at AS imported_at,
+  wd.updated_at AS updated_at,
+  wd.phonetic AS phonetic,
+  wd.part_of_speech_and_meanings AS part_of_speech_and_meanings,
+  wd.example_sentence AS example_sentence,
+  wd.example_translation AS example_translation,
+  wd.audio_uk AS audio_uk,
+  wd.audio_us AS audio_us,
+  (
+    wd.phonetic IS w.phonetic
+    AND wd.part_of_speech_and_meanings IS w.part_of_speech_and_meanings
+    AND wd.example_sentence IS w.example_sentence
+    AND wd.example_translation IS w.example_translation
+  ) AS is_displayed
+FROM word_definition wd
+JOIN word w ON w.id = wd.word_id
+JOIN word_list wl ON wl.id = wd.word_list_id
+JOIN dictionary_import di ON di.id = wd.import_id
+WHERE wd.word_id = ?
+ORDER BY wd.updated_at DESC, wd.import_id DESC
+"#,
+    )
+    .bind(word_id)
+    .fetch_all(pool)
+    .await
+    .context("读取单词释义来源失败")?;
+
+    let mut definitions = Vec::with_capacity(rows.len());
+    for row in rows {
+        definitions.push(WordDefinition {
+            word_list_id: row.try_get("word_list_id").context("读取释义词库失败")?,
+            word_list_name: row.try_get("word_list_name").context("读取释义词库失败")?,
+            source_kind: row.try_get("source_kind").context("读取释义来源失败")?,
+            source_name: row.try_get("source_name").context("读取释义来源失败")?,
+            imported_at: row.try_get("imported_at").context("读取导入时间失败")?,
+            updated_at: row.try_get("updated_at").context("读取释义更新时间失败")?,
+            phonetic: row.try_get("phonetic").context("读取释义失败")?,
+            part_of_speech_and_meanings: row
+                .try_get("part_of_speech_and_meanings")
+                .context("读取释义失败")?,
+            example_sentence: row.try_get("example_sentence").context("读取释义失败")?,
+            example_translation: row
+                .try_get("example_translation")
+                .context("读取释义失败")?,
+            audio_uk: row.try_get("audio_uk").context("读取释义失败")?,
+            audio_us: row.try_get("audio_us").context("读取释义失败")?,
+            is_displayed: row.try_get("is_displayed").context("读取释义失败")?,
+        });
+    }
+    Ok(definitions)
+}
+
+/// Lists every dictionary's definition of a word, newest first.
+pub async fn list_word_definitions(db: &Database, word_id: i64) -> Result<Vec<WordDefinition>> {
+    let pool = db.pool()?;
+    list_word_definitions_with_pool(&pool, word_id).await
 }
 
 async fn fetch_active_word_list_id(pool: &SqlitePool) -> Result<i64> {
@@ -875,6 +1572,10 @@ fn row_to_learning_word(row: sqlx::sqlite::SqliteRow) -> Result<LearningWord> {
         proficiency_score: row
             .try_get("proficiency_score")
             .context("Failed to read proficiency score")?,
+        due_at: row.try_get("due_at").context("Failed to read due time")?,
+        definition_source: row
+            .try_get("definition_source")
+            .context("Failed to read definition source")?,
     })
 }
 
@@ -883,28 +1584,28 @@ async fn fetch_words_with_condition(
     word_list_id: i64,
     condition: &str,
     exclude_ids: &[i64],
+    order_by: &str,
     limit: i64,
 ) -> Result<Vec<LearningWord>> {
     if limit <= 0 {
         return Ok(Vec::new());
     }
-    let mut builder = QueryBuilder::new(
+    let mut builder = QueryBuilder::new(format!(
         r#"
 SELECT
   w.id AS id,
   w.word AS word,
-  w.phonetic AS phonetic,
-  w.part_of_speech_and_meanings AS part_of_speech_and_meanings,
-  w.example_sentence AS example_sentence,
-  w.example_translation AS example_translation,
-  w.audio_uk AS audio_uk,
-  w.audio_us AS audio_us,
-  COALESCE(uwl.proficiency_score, 0) AS proficiency_score
+  {DEFINITION_COLUMNS},
+  COALESCE(uwl.proficiency_score, 0) AS proficiency_score,
+  uwl.due_at AS due_at,
+  {DEFINITION_SOURCE_COLUMN}
 FROM word w
 JOIN word_list_map wlm ON w.id = wlm.word_id
+JOIN word_list wl ON wl.id = wlm.word_list_id
+LEFT JOIN word_definition wd ON wd.word_id = w.id AND wd.word_list_id = wlm.word_list_id
 LEFT JOIN user_word_learning uwl ON w.id = uwl.word_id
-WHERE wlm.word_list_id = "#,
-    );
+WHERE wlm.word_list_id = "#
+    ));
     builder.push_bind(word_list_id);
     if !condition.trim().is_empty() {
         builder.push(" AND ");
@@ -918,7 +1619,9 @@ WHERE wlm.word_list_id = "#,
         }
         builder.push(")");
     }
-    builder.push(" ORDER BY RANDOM() LIMIT ");
+    builder.push(" ORDER BY ");
+    builder.push(order_by);
+    builder.push(" LIMIT ");
     builder.push_bind(limit);
 
     let rows = builder
@@ -930,76 +1633,203 @@ WHERE wlm.word_list_id = "#,
     rows.into_iter().map(row_to_learning_word).collect()
 }
 
-async fn allocate_learning_session_for_list(
-    pool: &SqlitePool,
-    word_list_id: i64,
-) -> Result<Vec<LearningWord>> {
-    let mut selected = Vec::new();
-    let mut selected_ids = Vec::new();
-
-    let unlearned = fetch_words_with_condition(
-        pool,
-        word_list_id,
-        "uwl.word_id IS NULL",
-        &selected_ids,
-        20,
+async fn write_session_profile(pool: &SqlitePool, profile: &SessionProfile) -> Result<()> {
+    sqlx::query(
+        r#"
+INSERT OR REPLACE INTO session_profile (
+  id,
+  session_size,
+  new_word_quota,
+  review_quota,
+  low_quota,
+  mid_quota,
+  high_quota,
+  low_score_below,
+  high_score_from,
+  updated_at
+)
+VALUES (1, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'))
+"#,
     )
-    .await?;
-    for word in unlearned {
-        selected_ids.push(word.id);
-        selected.push(word);
-    }
+    .bind(profile.session_size)
+    .bind(profile.new_word_quota)
+    .bind(profile.review_quota)
+    .bind(profile.low_quota)
+    .bind(profile.mid_quota)
+    .bind(profile.high_quota)
+    .bind(profile.low_score_below)
+    .bind(profile.high_score_from)
+    .execute(pool)
+    .await
+    .context("保存学习分组设置失败")?;
+    Ok(())
+}
 
-    let low = fetch_words_with_condition(
-        pool,
-        word_list_id,
-        "uwl.word_id IS NOT NULL AND uwl.proficiency_score < 4",
-        &selected_ids,
-        20,
+pub(crate) async fn read_session_profile(pool: &SqlitePool) -> Result<SessionProfile> {
+    let row = sqlx::query(
+        r#"
+SELECT
+  session_size,
+  new_word_quota,
+  review_quota,
+  low_quota,
+  mid_quota,
+  high_quota,
+  low_score_below,
+  high_score_from
+FROM session_profile
+WHERE id = 1
+"#,
     )
-    .await?;
-    for word in low {
-        selected_ids.push(word.id);
-        selected.push(word);
-    }
+    .fetch_optional(pool)
+    .await
+    .context("读取学习分组设置失败")?;
+    let Some(row) = row else {
+        return Ok(SessionProfile::default());
+    };
+    Ok(SessionProfile {
+        session_size: row.try_get("session_size").context("读取每轮单词数量失败")?,
+        new_word_quota: row.try_get("new_word_quota").context("读取新词数量失败")?,
+        review_quota: row.try_get("review_quota").context("读取复习数量失败")?,
+        low_quota: row.try_get("low_quota").context("读取低熟练度数量失败")?,
+        mid_quota: row.try_get("mid_quota").context("读取中熟练度数量失败")?,
+        high_quota: row.try_get("high_quota").context("读取高熟练度数量失败")?,
+        low_score_below: row
+            .try_get("low_score_below")
+            .context("读取低熟练度阈值失败")?,
+        high_score_from: row
+            .try_get("high_score_from")
+            .context("读取高熟练度阈值失败")?,
+    })
+}
 
-    let mid = fetch_words_with_condition(
-        pool,
-        word_list_id,
-        "uwl.word_id IS NOT NULL AND uwl.proficiency_score BETWEEN 4 AND 8",
-        &selected_ids,
-        6,
+async fn write_study_clock(pool: &SqlitePool, clock: &StudyClock) -> Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO study_clock (id, utc_offset_minutes, day_start_hour, updated_at) VALUES (1, ?, ?, datetime('now'))",
     )
-    .await?;
-    for word in mid {
-        selected_ids.push(word.id);
-        selected.push(word);
-    }
+    .bind(clock.utc_offset_minutes)
+    .bind(clock.day_start_hour)
+    .execute(pool)
+    .await
+    .context("保存学习日设置失败")?;
+    Ok(())
+}
 
-    let high = fetch_words_with_condition(
-        pool,
-        word_list_id,
-        "uwl.word_id IS NOT NULL AND uwl.proficiency_score BETWEEN 8 AND 10",
-        &selected_ids,
-        4,
+pub(crate) async fn read_study_clock(pool: &SqlitePool) -> Result<StudyClock> {
+    let row = sqlx::query(
+        "SELECT utc_offset_minutes, day_start_hour FROM study_clock WHERE id = 1",
     )
-    .await?;
-    for word in high {
-        selected_ids.push(word.id);
-        selected.push(word);
-    }
+    .fetch_optional(pool)
+    .await
+    .context("读取学习日设置失败")?;
+    let Some(row) = row else {
+        return Ok(StudyClock::default());
+    };
+    Ok(StudyClock {
+        utc_offset_minutes: row
+            .try_get("utc_offset_minutes")
+            .context("读取时区偏移失败")?,
+        day_start_hour: row
+            .try_get("day_start_hour")
+            .context("读取每日开始时间失败")?,
+    })
+}
+
+pub async fn get_study_clock(db: &Database) -> Result<StudyClock> {
+    let pool = db.pool()?;
+    read_study_clock(&pool).await
+}
+
+pub async fn update_study_clock(
+    db: &Database,
+    cache: &StudyCalendarCache,
+    clock: StudyClock,
+) -> Result<StudyClock> {
+    clock.validate()?;
+    let pool = db.pool()?;
+    write_study_clock(&pool, &clock).await?;
+    // Cached counts were grouped under the previous day boundaries.
+    cache.invalidate();
+    Ok(clock)
+}
+
+pub async fn get_session_profile(db: &Database) -> Result<SessionProfile> {
+    let pool = db.pool()?;
+    read_session_profile(&pool).await
+}
+
+pub async fn update_session_profile(
+    db: &Database,
+    profile: SessionProfile,
+) -> Result<SessionProfile> {
+    profile.validate()?;
+    let pool = db.pool()?;
+    write_session_profile(&pool, &profile).await?;
+    Ok(profile)
+}
+
+async fn select_session_words(pool: &SqlitePool, word_list_id: i64) -> Result<Vec<LearningWord>> {
+    let profile = read_session_profile(pool).await?;
+    let session_size = profile.session_size;
+    let mut selected = Vec::new();
+    let mut selected_ids = Vec::new();
 
-    let remaining = 50_i64.saturating_sub(selected.len() as i64);
-    if remaining > 0 {
-        let fill =
-            fetch_words_with_condition(pool, word_list_id, "1 = 1", &selected_ids, remaining)
-                .await?;
-        for word in fill {
+    // Overdue reviews come first, most overdue first. A review is due for the
+    // whole study day it falls on, not just from its exact timestamp.
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let due_condition = format!(
+        "uwl.due_at IS NOT NULL AND date(uwl.due_at, '{day_modifier}') <= date('now', '{day_modifier}')"
+    );
+    let low_condition = format!(
+        "uwl.word_id IS NOT NULL AND uwl.proficiency_score < {}",
+        profile.low_score_below
+    );
+    let mid_condition = format!(
+        "uwl.word_id IS NOT NULL AND uwl.proficiency_score >= {} AND uwl.proficiency_score < {}",
+        profile.low_score_below, profile.high_score_from
+    );
+    let high_condition = format!(
+        "uwl.word_id IS NOT NULL AND uwl.proficiency_score >= {}",
+        profile.high_score_from
+    );
+    let buckets = [
+        (
+            due_condition.as_str(),
+            "uwl.due_at ASC",
+            profile.review_quota,
+        ),
+        ("uwl.word_id IS NULL", "RANDOM()", profile.new_word_quota),
+        (low_condition.as_str(), "RANDOM()", profile.low_quota),
+        (mid_condition.as_str(), "RANDOM()", profile.mid_quota),
+        (high_condition.as_str(), "RANDOM()", profile.high_quota),
+        ("1 = 1", "RANDOM()", session_size),
+    ];
+
+    for (condition, order_by, quota) in buckets {
+        let remaining = session_size.saturating_sub(selected.len() as i64);
+        let words = fetch_words_with_condition(
+            pool,
+            word_list_id,
+            condition,
+            &selected_ids,
+            order_by,
+            quota.min(remaining),
+        )
+        .await?;
+        for word in words {
             selected_ids.push(word.id);
             selected.push(word);
         }
     }
 
+    Ok(selected)
+}
+
+async fn allocate_learning_session_for_list(
+    pool: &SqlitePool,
+    word_list_id: i64,
+) -> Result<Vec<LearningWord>> {
+    let selected = select_session_words(pool, word_list_id).await?;
     if selected.is_empty() {
         bail!("No words available in the active list.");
     }
@@ -1007,14 +1837,13 @@ async fn allocate_learning_session_for_list(
     Ok(selected)
 }
 
-pub async fn allocate_learning_session(app: &tauri::AppHandle) -> Result<Vec<LearningWord>> {
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
+pub async fn allocate_learning_session(db: &Database) -> Result<Vec<LearningWord>> {
+    let pool = db.pool()?;
     let word_list_id = fetch_active_word_list_id(&pool).await?;
     allocate_learning_session_for_list(&pool, word_list_id).await
 }
 
-async fn ensure_learning_row(pool: &SqlitePool, word_id: i64) -> Result<()> {
+async fn ensure_learning_row(conn: &mut SqliteConnection, word_id: i64) -> Result<()> {
     let result = sqlx::query(
         r#"
 INSERT OR IGNORE INTO user_word_learning (word_id, proficiency_score, last_learned_at, learn_count)
@@ -1024,7 +1853,7 @@ WHERE EXISTS (SELECT 1 FROM word WHERE id = ?)
     )
     .bind(word_id)
     .bind(word_id)
-    .execute(pool)
+    .execute(&mut *conn)
     .await
     .context("Failed to initialize learning row")?;
 
@@ -1033,7 +1862,7 @@ WHERE EXISTS (SELECT 1 FROM word WHERE id = ?)
             "SELECT 1 FROM user_word_learning WHERE word_id = ?",
         )
         .bind(word_id)
-        .fetch_optional(pool)
+        .fetch_optional(&mut *conn)
         .await
         .context("Failed to verify learning row")?;
         if exists.is_none() {
@@ -1043,12 +1872,12 @@ WHERE EXISTS (SELECT 1 FROM word WHERE id = ?)
     Ok(())
 }
 
-async fn read_learning_progress(pool: &SqlitePool, word_id: i64) -> Result<LearningProgress> {
+async fn read_learning_progress(conn: &mut SqliteConnection, word_id: i64) -> Result<LearningProgress> {
     let row = sqlx::query(
-        "SELECT proficiency_score, learn_count FROM user_word_learning WHERE word_id = ?",
+        "SELECT proficiency_score, learn_count, interval_days, due_at FROM user_word_learning WHERE word_id = ?",
     )
     .bind(word_id)
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
     .await
     .context("Failed to read learning progress")?;
     Ok(LearningProgress {
@@ -1059,173 +1888,789 @@ async fn read_learning_progress(pool: &SqlitePool, word_id: i64) -> Result<Learn
         learn_count: row
             .try_get("learn_count")
             .context("Failed to read learn count")?,
+        interval_days: row
+            .try_get("interval_days")
+            .context("Failed to read review interval")?,
+        due_at: row.try_get("due_at").context("Failed to read due time")?,
     })
 }
 
-async fn record_study_event(pool: &SqlitePool, word_id: i64) -> Result<()> {
-    sqlx::query("INSERT INTO study_log (word_id, learned_at) VALUES (?, datetime('now'))")
-        .bind(word_id)
-        .execute(pool)
-        .await
-        .context("Failed to insert study log")?;
-    Ok(())
+async fn read_review_state(conn: &mut SqliteConnection, word_id: i64) -> Result<ReviewState> {
+    let row = sqlx::query(
+        "SELECT ease_factor, interval_days, repetitions, lapses FROM user_word_learning WHERE word_id = ?",
+    )
+    .bind(word_id)
+    .fetch_one(&mut *conn)
+    .await
+    .context("Failed to read review state")?;
+    Ok(ReviewState {
+        ease_factor: row
+            .try_get("ease_factor")
+            .context("Failed to read ease factor")?,
+        interval_days: row
+            .try_get("interval_days")
+            .context("Failed to read review interval")?,
+        repetitions: row
+            .try_get("repetitions")
+            .context("Failed to read repetitions")?,
+        lapses: row.try_get("lapses").context("Failed to read lapses")?,
+    })
 }
 
-async fn increment_proficiency_for_word(
-    pool: &SqlitePool,
+async fn record_study_event(
+    conn: &mut SqliteConnection,
     word_id: i64,
-) -> Result<LearningProgress> {
-    ensure_learning_row(pool, word_id).await?;
-    sqlx::query(
+    grade: ReviewGrade,
+    event: &StudyEvent,
+    previous_score: Option<i64>,
+    new_score: i64,
+) -> Result<i64> {
+    let result = sqlx::query(
         r#"
-UPDATE user_word_learning
-SET proficiency_score = MIN(10, proficiency_score + 1),
-    learn_count = learn_count + 1,
-    last_learned_at = datetime('now')
+INSERT INTO study_log (
+  word_id,
+  learned_at,
+  grade,
+  event_type,
+  previous_score,
+  new_score,
+  response_ms,
+  session_id,
+  study_mode
+)
+VALUES (?, datetime('now'), ?, ?, ?, ?, ?, ?, ?)
+"#,
+    )
+    .bind(word_id)
+    .bind(grade.as_str())
+    .bind(event.kind.as_str())
+    .bind(previous_score)
+    .bind(new_score)
+    .bind(event.context.response_ms)
+    .bind(event.session_id)
+    .bind(event.context.study_mode.as_deref())
+    .execute(&mut *conn)
+    .await
+    .context("写入学习记录失败")?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Snapshots the learning row before a grade is applied so it can be undone.
+async fn record_grade_history(
+    conn: &mut SqliteConnection,
+    word_id: i64,
+    had_learning_row: bool,
+) -> Result<i64> {
+    let result = sqlx::query(
+        r#"
+INSERT INTO grade_history (
+  word_id,
+  had_learning_row,
+  proficiency_score,
+  last_learned_at,
+  learn_count,
+  is_fuzzy,
+  fuzzy_marked_at,
+  ease_factor,
+  interval_days,
+  repetitions,
+  lapses,
+  due_at
+)
+SELECT
+  word_id,
+  ?,
+  proficiency_score,
+  last_learned_at,
+  learn_count,
+  is_fuzzy,
+  fuzzy_marked_at,
+  ease_factor,
+  interval_days,
+  repetitions,
+  lapses,
+  due_at
+FROM user_word_learning
 WHERE word_id = ?
 "#,
     )
+    .bind(had_learning_row)
     .bind(word_id)
-    .execute(pool)
+    .execute(&mut *conn)
     .await
-    .context("Failed to increment proficiency")?;
-    record_study_event(pool, word_id).await?;
-    read_learning_progress(pool, word_id).await
+    .context("记录评分历史失败")?;
+    let history_id = result.last_insert_rowid();
+
+    sqlx::query("DELETE FROM grade_history WHERE id <= ?")
+        .bind(history_id - GRADE_HISTORY_LIMIT)
+        .execute(&mut *conn)
+        .await
+        .context("清理评分历史失败")?;
+    Ok(history_id)
 }
 
-async fn decrement_proficiency_for_word(
-    pool: &SqlitePool,
+async fn apply_schedule(
+    conn: &mut SqliteConnection,
     word_id: i64,
+    grade: ReviewGrade,
+    event: &StudyEvent,
 ) -> Result<LearningProgress> {
-    ensure_learning_row(pool, word_id).await?;
+    let previous_score: Option<i64> =
+        sqlx::query_scalar("SELECT proficiency_score FROM user_word_learning WHERE word_id = ?")
+            .bind(word_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .context("读取学习进度失败")?;
+    ensure_learning_row(conn, word_id).await?;
+    let history_id = record_grade_history(conn, word_id, previous_score.is_some()).await?;
+    let next = scheduler::schedule(&read_review_state(conn, word_id).await?, grade.quality());
     sqlx::query(
         r#"
 UPDATE user_word_learning
-SET proficiency_score = MAX(0, proficiency_score - 1),
-    last_learned_at = datetime('now')
+SET proficiency_score = MAX(0, MIN(10, proficiency_score + ?)),
+    learn_count = learn_count + ?,
+    last_learned_at = datetime('now'),
+    ease_factor = ?,
+    interval_days = ?,
+    repetitions = ?,
+    lapses = ?,
+    due_at = datetime('now', ?)
 WHERE word_id = ?
 "#,
     )
+    .bind(grade.score_delta())
+    .bind(i64::from(!grade.is_lapse()))
+    .bind(next.ease_factor)
+    .bind(next.interval_days)
+    .bind(next.repetitions)
+    .bind(next.lapses)
+    .bind(scheduler::due_modifier(next.interval_days))
     .bind(word_id)
-    .execute(pool)
+    .execute(&mut *conn)
     .await
-    .context("Failed to decrement proficiency")?;
-    record_study_event(pool, word_id).await?;
-    read_learning_progress(pool, word_id).await
+    .context("更新学习进度失败")?;
+    let progress = read_learning_progress(conn, word_id).await?;
+    let study_log_id = record_study_event(
+        conn,
+        word_id,
+        grade,
+        event,
+        previous_score,
+        progress.proficiency_score,
+    )
+    .await?;
+    sqlx::query("UPDATE grade_history SET study_log_id = ? WHERE id = ?")
+        .bind(study_log_id)
+        .bind(history_id)
+        .execute(&mut *conn)
+        .await
+        .context("关联评分历史失败")?;
+    Ok(progress)
+}
+
+async fn increment_proficiency_for_word(
+    pool: &SqlitePool,
+    word_id: i64,
+    context: StudyEventContext,
+) -> Result<LearningProgress> {
+    let event = StudyEvent::new(StudyEventKind::Correct, context);
+    grade_word_with_pool(pool, word_id, ReviewGrade::Good, &event).await
+}
+
+async fn decrement_proficiency_for_word(
+    pool: &SqlitePool,
+    word_id: i64,
+    context: StudyEventContext,
+) -> Result<LearningProgress> {
+    let event = StudyEvent::new(StudyEventKind::Incorrect, context);
+    grade_word_with_pool(pool, word_id, ReviewGrade::Again, &event).await
 }
 
 pub async fn increment_proficiency(
-    app: &tauri::AppHandle,
+    db: &Database,
     cache: &StudyCalendarCache,
     word_id: i64,
+    context: StudyEventContext,
 ) -> Result<LearningProgress> {
     if word_id <= 0 {
         bail!("Invalid word id");
     }
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
-    let progress = increment_proficiency_for_word(&pool, word_id).await?;
+    let context = context.normalized()?;
+    let pool = db.pool()?;
+    let progress = increment_proficiency_for_word(&pool, word_id, context).await?;
     cache.invalidate();
     Ok(progress)
 }
 
 pub async fn decrement_proficiency(
-    app: &tauri::AppHandle,
+    db: &Database,
     cache: &StudyCalendarCache,
     word_id: i64,
+    context: StudyEventContext,
 ) -> Result<LearningProgress> {
     if word_id <= 0 {
         bail!("Invalid word id");
     }
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
-    let progress = decrement_proficiency_for_word(&pool, word_id).await?;
-    mark_word_fuzzy(&pool, word_id).await?;
-    sync_fuzzy_word_list(&pool).await?;
+    let context = context.normalized()?;
+    let pool = db.pool()?;
+    let progress = decrement_proficiency_for_word(&pool, word_id, context).await?;
     cache.invalidate();
     Ok(progress)
 }
 
-async fn list_daily_study_counts_internal(pool: &SqlitePool) -> Result<Vec<DailyStudyCount>> {
+pub async fn grade_word(
+    db: &Database,
+    cache: &StudyCalendarCache,
+    word_id: i64,
+    grade: ReviewGrade,
+    context: StudyEventContext,
+) -> Result<LearningProgress> {
+    if word_id <= 0 {
+        bail!("Invalid word id");
+    }
+    let event = StudyEvent::new(StudyEventKind::Grade, context.normalized()?);
+    let pool = db.pool()?;
+    let progress = grade_word_with_pool(&pool, word_id, grade, &event).await?;
+    cache.invalidate();
+    Ok(progress)
+}
+
+/// Schedules the word and marks lapses fuzzy on the caller's connection. The
+/// fuzzy word list is synced by the caller once its writes are committed.
+async fn apply_grade(
+    conn: &mut SqliteConnection,
+    word_id: i64,
+    grade: ReviewGrade,
+    event: &StudyEvent,
+) -> Result<LearningProgress> {
+    let progress = apply_schedule(conn, word_id, grade, event).await?;
+    if grade.is_lapse() {
+        mark_word_fuzzy(conn, word_id).await?;
+    }
+    Ok(progress)
+}
+
+async fn grade_word_with_pool(
+    pool: &SqlitePool,
+    word_id: i64,
+    grade: ReviewGrade,
+    event: &StudyEvent,
+) -> Result<LearningProgress> {
+    // The learning row, its history snapshot and the study log entry are
+    // written together, so undo never finds half of a grade.
+    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+    let progress = apply_grade(&mut tx, word_id, grade, event).await?;
+    tx.commit().await.context("提交数据库事务失败")?;
+    if grade.is_lapse() {
+        sync_fuzzy_word_list(pool).await?;
+    }
+    Ok(progress)
+}
+
+async fn undo_last_grades(pool: &SqlitePool, count: i64) -> Result<UndoSummary> {
+    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
     let rows = sqlx::query(
-        r#"
-SELECT date(learned_at) AS date, COUNT(DISTINCT word_id) AS word_count
-FROM study_log
-GROUP BY date(learned_at)
-ORDER BY date(learned_at) ASC
-"#,
+        "SELECT id, word_id, study_log_id, had_learning_row FROM grade_history ORDER BY id DESC LIMIT ?",
     )
-    .fetch_all(pool)
+    .bind(count)
+    .fetch_all(&mut *tx)
     .await
-    .context("Failed to read daily study counts")?;
+    .context("读取评分历史失败")?;
+
+    let mut word_ids = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let history_id: i64 = row.try_get("id").context("读取评分历史 ID 失败")?;
+        let word_id: i64 = row.try_get("word_id").context("读取单词 ID 失败")?;
+        let study_log_id: Option<i64> = row
+            .try_get("study_log_id")
+            .context("读取学习记录 ID 失败")?;
+        let had_learning_row: bool = row
+            .try_get("had_learning_row")
+            .context("读取评分历史失败")?;
+
+        if had_learning_row {
+            sqlx::query(
+                r#"
+UPDATE user_word_learning
+SET (
+  proficiency_score,
+  last_learned_at,
+  learn_count,
+  is_fuzzy,
+  fuzzy_marked_at,
+  ease_factor,
+  interval_days,
+  repetitions,
+  lapses,
+  due_at
+) = (
+  SELECT
+    proficiency_score,
+    last_learned_at,
+    learn_count,
+    is_fuzzy,
+    fuzzy_marked_at,
+    ease_factor,
+    interval_days,
+    repetitions,
+    lapses,
+    due_at
+  FROM grade_history
+  WHERE id = ?
+)
+WHERE word_id = ?
+"#,
+            )
+            .bind(history_id)
+            .bind(word_id)
+            .execute(&mut *tx)
+            .await
+            .context("恢复学习进度失败")?;
+        } else {
+            sqlx::query("DELETE FROM user_word_learning WHERE word_id = ?")
+                .bind(word_id)
+                .execute(&mut *tx)
+                .await
+                .context("恢复学习进度失败")?;
+        }
 
-    let mut counts = Vec::with_capacity(rows.len());
-    for row in rows {
-        let date: String = row.try_get("date").context("Failed to read date")?;
-        let word_count: i64 = row
-            .try_get("word_count")
-            .context("Failed to read word count")?;
-        counts.push(DailyStudyCount { date, word_count });
+        if let Some(study_log_id) = study_log_id {
+            let session_id: Option<i64> =
+                sqlx::query_scalar("SELECT session_id FROM study_log WHERE id = ?")
+                    .bind(study_log_id)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .context("读取学习记录失败")?
+                    .flatten();
+            if let Some(session_id) = session_id {
+                revert_session_answer(&mut tx, session_id, word_id).await?;
+            }
+
+            sqlx::query("DELETE FROM study_log WHERE id = ?")
+                .bind(study_log_id)
+                .execute(&mut *tx)
+                .await
+                .context("撤销学习记录失败")?;
+        }
+
+        sqlx::query("DELETE FROM grade_history WHERE id = ?")
+            .bind(history_id)
+            .execute(&mut *tx)
+            .await
+            .context("清理评分历史失败")?;
+        word_ids.push(word_id);
     }
-    Ok(counts)
+
+    tx.commit().await.context("提交数据库事务失败")?;
+    sync_fuzzy_word_list(pool).await?;
+    Ok(UndoSummary {
+        undone: word_ids.len() as u64,
+        word_ids,
+    })
 }
 
-pub async fn list_daily_study_counts(
-    app: &tauri::AppHandle,
+/// Clears the latest graded answer for the word in its session and moves the
+/// cursor back to it. A finished session is reopened unless another session
+/// has started since.
+async fn revert_session_answer(
+    conn: &mut SqliteConnection,
+    session_id: i64,
+    word_id: i64,
+) -> Result<()> {
+    let position: Option<i64> = sqlx::query_scalar(
+        "SELECT MAX(position) FROM learning_session_item WHERE session_id = ? AND word_id = ? AND grade IS NOT NULL",
+    )
+    .bind(session_id)
+    .bind(word_id)
+    .fetch_one(&mut *conn)
+    .await
+    .context("读取会话作答失败")?;
+    let Some(position) = position else {
+        return Ok(());
+    };
+
+    sqlx::query(
+        "UPDATE learning_session_item SET grade = NULL, answered_at = NULL WHERE session_id = ? AND position = ?",
+    )
+    .bind(session_id)
+    .bind(position)
+    .execute(&mut *conn)
+    .await
+    .context("撤销会话作答失败")?;
+
+    sqlx::query(
+        r#"
+UPDATE learning_session
+SET cursor = MIN(cursor, ?),
+    updated_at = datetime('now'),
+    status = CASE
+      WHEN status = 'finished' AND NOT EXISTS (SELECT 1 FROM learning_session WHERE status = 'active') THEN 'active'
+      ELSE status
+    END,
+    finished_at = CASE
+      WHEN status = 'finished' AND NOT EXISTS (SELECT 1 FROM learning_session WHERE status = 'active') THEN NULL
+      ELSE finished_at
+    END
+WHERE id = ?
+"#,
+    )
+    .bind(position)
+    .bind(session_id)
+    .execute(&mut *conn)
+    .await
+    .context("恢复会话进度失败")?;
+    Ok(())
+}
+
+pub async fn undo_last_grade(
+    db: &Database,
     cache: &StudyCalendarCache,
-) -> Result<Vec<DailyStudyCount>> {
-    if let Some(cached) = cache.get() {
-        return Ok(cached);
+    count: Option<i64>,
+) -> Result<UndoSummary> {
+    let count = count.unwrap_or(1);
+    if !(1..=GRADE_HISTORY_LIMIT).contains(&count) {
+        bail!("撤销次数需在 1 到 {GRADE_HISTORY_LIMIT} 之间");
     }
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
-    let counts = list_daily_study_counts_internal(&pool).await?;
-    cache.set(counts.clone());
-    Ok(counts)
+    let pool = db.pool()?;
+    let summary = undo_last_grades(&pool, count).await?;
+    cache.invalidate();
+    Ok(summary)
 }
 
-pub async fn list_fuzzy_words(
-    app: &tauri::AppHandle,
-    sort: Option<String>,
-) -> Result<Vec<FuzzyWordItem>> {
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
-    sync_fuzzy_word_list(&pool).await?;
+async fn create_learning_session_for_list(
+    pool: &SqlitePool,
+    word_list_id: i64,
+) -> Result<LearningSession> {
+    // Check before touching the current session, so an empty list leaves it
+    // active instead of abandoning it for nothing.
+    let words = select_session_words(pool, word_list_id).await?;
+    if words.is_empty() {
+        bail!("词库中没有可学习的单词");
+    }
 
-    let order_by = match sort.as_deref() {
-        Some("alpha") => "w.word COLLATE NOCASE ASC",
-        _ => "uwl.fuzzy_marked_at DESC, w.word COLLATE NOCASE ASC",
+    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+    sqlx::query(
+        "UPDATE learning_session SET status = 'abandoned', finished_at = datetime('now'), updated_at = datetime('now') WHERE status = 'active'",
+    )
+    .execute(&mut *tx)
+    .await
+    .context("结束旧学习会话失败")?;
+
+    let session_id = sqlx::query("INSERT INTO learning_session (word_list_id) VALUES (?)")
+        .bind(word_list_id)
+        .execute(&mut *tx)
+        .await
+        .context("创建学习会话失败")?
+        .last_insert_rowid();
+
+    let mut builder = QueryBuilder::new(
+        "INSERT INTO learning_session_item (session_id, position, word_id) ",
+    );
+    builder.push_values(words.iter().enumerate(), |mut row, (position, word)| {
+        row.push_bind(session_id)
+            .push_bind(position as i64)
+            .push_bind(word.id);
+    });
+    builder
+        .build()
+        .execute(&mut *tx)
+        .await
+        .context("写入学习会话单词失败")?;
+
+    tx.commit().await.context("提交数据库事务失败")?;
+    read_learning_session(pool, session_id).await
+}
+
+async fn read_learning_session(pool: &SqlitePool, session_id: i64) -> Result<LearningSession> {
+    let row = sqlx::query(
+        "SELECT id, word_list_id, cursor, status, created_at, finished_at FROM learning_session WHERE id = ?",
+    )
+    .bind(session_id)
+    .fetch_optional(pool)
+    .await
+    .context("读取学习会话失败")?;
+    let Some(row) = row else {
+        bail!("学习会话不存在");
     };
 
-    let query = format!(
+    let card_query = format!(
         r#"
 SELECT
+  lsi.position AS position,
+  lsi.grade AS grade,
+  lsi.answered_at AS answered_at,
   w.id AS id,
   w.word AS word,
-  w.phonetic AS phonetic,
-  w.part_of_speech_and_meanings AS part_of_speech_and_meanings,
-  w.example_sentence AS example_sentence,
-  w.example_translation AS example_translation,
-  w.audio_uk AS audio_uk,
-  w.audio_us AS audio_us,
-  uwl.fuzzy_marked_at AS fuzzy_marked_at
-FROM word w
-JOIN user_word_learning uwl ON w.id = uwl.word_id
-WHERE uwl.is_fuzzy = 1
-ORDER BY {order_by}
+  {DEFINITION_COLUMNS},
+  COALESCE(uwl.proficiency_score, 0) AS proficiency_score,
+  uwl.due_at AS due_at,
+  {DEFINITION_SOURCE_COLUMN}
+FROM learning_session_item lsi
+JOIN learning_session ls ON ls.id = lsi.session_id
+JOIN word w ON w.id = lsi.word_id
+LEFT JOIN word_list wl ON wl.id = ls.word_list_id
+LEFT JOIN word_definition wd ON wd.word_id = w.id AND wd.word_list_id = ls.word_list_id
+LEFT JOIN user_word_learning uwl ON w.id = uwl.word_id
+WHERE lsi.session_id = ?
+ORDER BY lsi.position ASC
 "#
     );
-
-    let rows = sqlx::query(&query)
-        .fetch_all(&pool)
+    let card_rows = sqlx::query(&card_query)
+        .bind(session_id)
+        .fetch_all(pool)
         .await
-        .context("读取模糊词列表失败")?;
+        .context("读取学习会话单词失败")?;
+
+    let mut cards = Vec::with_capacity(card_rows.len());
+    for card_row in card_rows {
+        let position: i64 = card_row.try_get("position").context("读取会话位置失败")?;
+        let grade: Option<String> = card_row.try_get("grade").context("读取会话评分失败")?;
+        let answered_at: Option<String> = card_row
+            .try_get("answered_at")
+            .context("读取会话作答时间失败")?;
+        cards.push(LearningSessionCard {
+            position,
+            grade,
+            answered_at,
+            word: row_to_learning_word(card_row)?,
+        });
+    }
 
-    let mut words = Vec::with_capacity(rows.len());
-    for row in rows {
-        words.push(FuzzyWordItem {
-            id: row.try_get("id").context("读取单词 ID 失败")?,
-            word: row.try_get("word").context("读取单词失败")?,
+    Ok(LearningSession {
+        id: row.try_get("id").context("读取学习会话 ID 失败")?,
+        word_list_id: row.try_get("word_list_id").context("读取会话词库失败")?,
+        cursor: row.try_get("cursor").context("读取会话进度失败")?,
+        status: row.try_get("status").context("读取会话状态失败")?,
+        created_at: row.try_get("created_at").context("读取会话创建时间失败")?,
+        finished_at: row.try_get("finished_at").context("读取会话结束时间失败")?,
+        cards,
+    })
+}
+
+async fn fetch_active_learning_session(pool: &SqlitePool) -> Result<Option<LearningSession>> {
+    let session_id: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM learning_session WHERE status = 'active' ORDER BY id DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .context("读取当前学习会话失败")?;
+    match session_id {
+        Some(id) => Ok(Some(read_learning_session(pool, id).await?)),
+        None => Ok(None),
+    }
+}
+
+async fn advance_learning_session_for_id(
+    pool: &SqlitePool,
+    session_id: i64,
+    grade: Option<ReviewGrade>,
+    context: StudyEventContext,
+) -> Result<LearningSessionAdvance> {
+    // Grading the card, recording the answer and moving the cursor commit
+    // together, so a resumed session never grades the same card twice.
+    let mut tx = pool.begin().await.context("开启数据库事务失败")?;
+    let row = sqlx::query("SELECT cursor, status FROM learning_session WHERE id = ?")
+        .bind(session_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("读取学习会话失败")?;
+    let Some(row) = row else {
+        bail!("学习会话不存在");
+    };
+    let cursor: i64 = row.try_get("cursor").context("读取会话进度失败")?;
+    let status: String = row.try_get("status").context("读取会话状态失败")?;
+    if status != "active" {
+        bail!("学习会话已结束");
+    }
+
+    let word_id: Option<i64> = sqlx::query_scalar(
+        "SELECT word_id FROM learning_session_item WHERE session_id = ? AND position = ?",
+    )
+    .bind(session_id)
+    .bind(cursor)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("读取会话单词失败")?;
+    let Some(word_id) = word_id else {
+        bail!("学习会话已没有剩余单词");
+    };
+
+    let progress = match grade {
+        Some(grade) => {
+            let mut event = StudyEvent::new(StudyEventKind::Grade, context);
+            event.session_id = Some(session_id);
+            event
+                .context
+                .study_mode
+                .get_or_insert_with(|| "session".to_string());
+            Some(apply_grade(&mut tx, word_id, grade, &event).await?)
+        }
+        None => None,
+    };
+
+    sqlx::query(
+        "UPDATE learning_session_item SET grade = ?, answered_at = datetime('now') WHERE session_id = ? AND position = ?",
+    )
+    .bind(grade.map(ReviewGrade::as_str))
+    .bind(session_id)
+    .bind(cursor)
+    .execute(&mut *tx)
+    .await
+    .context("记录会话作答失败")?;
+
+    sqlx::query(
+        r#"
+UPDATE learning_session
+SET cursor = cursor + 1,
+    updated_at = datetime('now'),
+    status = CASE
+      WHEN cursor + 1 >= (SELECT COUNT(1) FROM learning_session_item WHERE session_id = ?) THEN 'finished'
+      ELSE status
+    END,
+    finished_at = CASE
+      WHEN cursor + 1 >= (SELECT COUNT(1) FROM learning_session_item WHERE session_id = ?) THEN datetime('now')
+      ELSE finished_at
+    END
+WHERE id = ?
+"#,
+    )
+    .bind(session_id)
+    .bind(session_id)
+    .bind(session_id)
+    .execute(&mut *tx)
+    .await
+    .context("更新会话进度失败")?;
+    tx.commit().await.context("提交数据库事务失败")?;
+
+    if grade.is_some_and(ReviewGrade::is_lapse) {
+        sync_fuzzy_word_list(pool).await?;
+    }
+
+    Ok(LearningSessionAdvance {
+        session: read_learning_session(pool, session_id).await?,
+        progress,
+    })
+}
+
+pub async fn create_learning_session(db: &Database) -> Result<LearningSession> {
+    let pool = db.pool()?;
+    let word_list_id = fetch_active_word_list_id(&pool).await?;
+    create_learning_session_for_list(&pool, word_list_id).await
+}
+
+pub async fn get_learning_session(db: &Database) -> Result<Option<LearningSession>> {
+    let pool = db.pool()?;
+    fetch_active_learning_session(&pool).await
+}
+
+pub async fn advance_learning_session(
+    db: &Database,
+    cache: &StudyCalendarCache,
+    session_id: i64,
+    grade: Option<ReviewGrade>,
+    context: StudyEventContext,
+) -> Result<LearningSessionAdvance> {
+    let context = context.normalized()?;
+    let pool = db.pool()?;
+    let advance = advance_learning_session_for_id(&pool, session_id, grade, context).await?;
+    if advance.progress.is_some() {
+        cache.invalidate();
+    }
+    Ok(advance)
+}
+
+pub async fn finish_learning_session(db: &Database, session_id: i64) -> Result<()> {
+    let pool = db.pool()?;
+    sqlx::query(
+        "UPDATE learning_session SET status = 'finished', finished_at = datetime('now'), updated_at = datetime('now') WHERE id = ? AND status = 'active'",
+    )
+    .bind(session_id)
+    .execute(&pool)
+    .await
+    .context("结束学习会话失败")?;
+    Ok(())
+}
+
+async fn list_daily_study_counts_internal(pool: &SqlitePool) -> Result<Vec<DailyStudyCount>> {
+    let day_modifier = read_study_clock(pool).await?.day_modifier();
+    let rows = sqlx::query(
+        r#"
+SELECT date(learned_at, ?) AS study_day, COUNT(DISTINCT word_id) AS word_count
+FROM study_log
+GROUP BY study_day
+ORDER BY study_day ASC
+"#,
+    )
+    .bind(&day_modifier)
+    .fetch_all(pool)
+    .await
+    .context("Failed to read daily study counts")?;
+
+    let mut counts = Vec::with_capacity(rows.len());
+    for row in rows {
+        let date: String = row.try_get("study_day").context("Failed to read date")?;
+        let word_count: i64 = row
+            .try_get("word_count")
+            .context("Failed to read word count")?;
+        counts.push(DailyStudyCount { date, word_count });
+    }
+    Ok(counts)
+}
+
+pub async fn list_daily_study_counts(
+    db: &Database,
+    cache: &StudyCalendarCache,
+) -> Result<Vec<DailyStudyCount>> {
+    if let Some(cached) = cache.get() {
+        return Ok(cached);
+    }
+    let pool = db.pool()?;
+    let counts = list_daily_study_counts_internal(&pool).await?;
+    cache.set(counts.clone());
+    Ok(counts)
+}
+
+pub async fn list_fuzzy_words(
+    db: &Database,
+    sort: Option<String>,
+) -> Result<Vec<FuzzyWordItem>> {
+    let pool = db.pool()?;
+    sync_fuzzy_word_list(&pool).await?;
+
+    let order_by = match sort.as_deref() {
+        Some("alpha") => "w.word COLLATE NOCASE ASC",
+        _ => "uwl.fuzzy_marked_at DESC, w.word COLLATE NOCASE ASC",
+    };
+
+    let query = format!(
+        r#"
+SELECT
+  w.id AS id,
+  w.word AS word,
+  w.phonetic AS phonetic,
+  w.part_of_speech_and_meanings AS part_of_speech_and_meanings,
+  w.example_sentence AS example_sentence,
+  w.example_translation AS example_translation,
+  w.audio_uk AS audio_uk,
+  w.audio_us AS audio_us,
+  uwl.fuzzy_marked_at AS fuzzy_marked_at
+FROM word w
+JOIN user_word_learning uwl ON w.id = uwl.word_id
+WHERE uwl.is_fuzzy = 1
+ORDER BY {order_by}
+"#
+    );
+
+    let rows = sqlx::query(&query)
+        .fetch_all(&pool)
+        .await
+        .context("读取模糊词列表失败")?;
+
+    let mut words = Vec::with_capacity(rows.len());
+    for row in rows {
+        words.push(FuzzyWordItem {
+            id: row.try_get("id").context("读取单词 ID 失败")?,
+            word: row.try_get("word").context("读取单词失败")?,
             phonetic: row.try_get("phonetic").context("读取音标失败")?,
             part_of_speech_and_meanings: row
                 .try_get("part_of_speech_and_meanings")
@@ -1248,7 +2693,7 @@ ORDER BY {order_by}
 }
 
 pub async fn clear_fuzzy_marks(
-    app: &tauri::AppHandle,
+    db: &Database,
     word_ids: Vec<i64>,
 ) -> Result<()> {
     let ids: Vec<i64> = word_ids.into_iter().filter(|id| *id > 0).collect();
@@ -1256,8 +2701,7 @@ pub async fn clear_fuzzy_marks(
         return Ok(());
     }
 
-    let pool = open_pool(app).await?;
-    ensure_schema(&pool).await?;
+    let pool = db.pool()?;
 
     let mut builder = QueryBuilder::new(
         "UPDATE user_word_learning SET is_fuzzy = 0, fuzzy_marked_at = NULL WHERE word_id IN (",
@@ -1281,6 +2725,7 @@ pub async fn clear_fuzzy_marks(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dictionary_import::{import_dictionary_csv_with_pool, CsvImportOptions};
     use tauri::async_runtime;
 
     async fn setup_pool() -> SqlitePool {
@@ -1299,6 +2744,33 @@ mod tests {
         pool
     }
 
+    /// Returns the word's fuzzy flag and whether it sits in the fuzzy word list.
+    async fn fuzzy_state(pool: &SqlitePool, word_id: i64) -> (i64, i64) {
+        sqlx::query_as(
+            r#"
+SELECT
+  uwl.is_fuzzy,
+  (
+    SELECT COUNT(1)
+    FROM word_list_map wlm
+    JOIN word_list wl ON wl.id = wlm.word_list_id
+    WHERE wl.name = ? AND wlm.word_id = uwl.word_id
+  )
+FROM user_word_learning uwl
+WHERE uwl.word_id = ?
+"#,
+        )
+        .bind(FUZZY_WORD_LIST_NAME)
+        .bind(word_id)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to read fuzzy state")
+    }
+
+    fn graded() -> StudyEvent {
+        StudyEvent::new(StudyEventKind::Grade, StudyEventContext::default())
+    }
+
     async fn insert_word(pool: &SqlitePool, word: &str) -> i64 {
         let result = sqlx::query(
             r#"
@@ -1335,13 +2807,19 @@ VALUES (?, NULL, NULL, NULL, NULL, NULL, NULL)
         async_runtime::block_on(async {
             let pool = setup_pool().await;
             let word_id = insert_word(&pool, "alpha").await;
-            ensure_learning_row(&pool, word_id)
+            let mut conn = pool.acquire().await.expect("Failed to acquire connection");
+            ensure_learning_row(&mut conn, word_id)
                 .await
                 .expect("Failed to ensure learning row");
+            drop(conn);
 
-            let progress = increment_proficiency_for_word(&pool, word_id)
-                .await
-                .expect("Failed to increment");
+            let progress = increment_proficiency_for_word(
+                &pool,
+                word_id,
+                StudyEventContext::default(),
+            )
+            .await
+            .expect("Failed to increment");
             assert_eq!(progress.proficiency_score, 1);
 
             sqlx::query("UPDATE user_word_learning SET proficiency_score = 10 WHERE word_id = ?")
@@ -1349,9 +2827,13 @@ VALUES (?, NULL, NULL, NULL, NULL, NULL, NULL)
                 .execute(&pool)
                 .await
                 .expect("Failed to update score");
-            let progress = increment_proficiency_for_word(&pool, word_id)
-                .await
-                .expect("Failed to increment at max");
+            let progress = increment_proficiency_for_word(
+                &pool,
+                word_id,
+                StudyEventContext::default(),
+            )
+            .await
+            .expect("Failed to increment at max");
             assert_eq!(progress.proficiency_score, 10);
 
             sqlx::query("UPDATE user_word_learning SET proficiency_score = 0 WHERE word_id = ?")
@@ -1359,9 +2841,13 @@ VALUES (?, NULL, NULL, NULL, NULL, NULL, NULL)
                 .execute(&pool)
                 .await
                 .expect("Failed to reset score");
-            let progress = decrement_proficiency_for_word(&pool, word_id)
-                .await
-                .expect("Failed to decrement at min");
+            let progress = decrement_proficiency_for_word(
+                &pool,
+                word_id,
+                StudyEventContext::default(),
+            )
+            .await
+            .expect("Failed to decrement at min");
             assert_eq!(progress.proficiency_score, 0);
         });
     }
@@ -1463,6 +2949,720 @@ VALUES (?, NULL, NULL, NULL, NULL, NULL, NULL)
             assert_eq!(high, 4);
         });
     }
-}
 
+    #[test]
+    fn overdue_words_are_allocated_first() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let list_id = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("list-a")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+
+            let mut ids = Vec::new();
+            for i in 0..60 {
+                let word_id = insert_word(&pool, &format!("word_{i}")).await;
+                map_word(&pool, list_id, word_id).await;
+                ids.push(word_id);
+            }
+            for (offset, word_id) in ids[..5].iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO user_word_learning (word_id, proficiency_score, learn_count, due_at) VALUES (?, 9, 3, datetime('now', ?))",
+                )
+                .bind(*word_id)
+                .bind(format!("-{} days", offset + 1))
+                .execute(&pool)
+                .await
+                .expect("Failed to insert overdue word");
+            }
+            for word_id in &ids[5..10] {
+                sqlx::query(
+                    "INSERT INTO user_word_learning (word_id, proficiency_score, learn_count, due_at) VALUES (?, 9, 3, datetime('now', '+3 days'))",
+                )
+                .bind(*word_id)
+                .execute(&pool)
+                .await
+                .expect("Failed to insert scheduled word");
+            }
+
+            let session = allocate_learning_session_for_list(&pool, list_id)
+                .await
+                .expect("Failed to allocate session");
+            assert_eq!(session.len(), 50);
+            let leading: Vec<i64> = session.iter().take(5).map(|word| word.id).collect();
+            let expected: Vec<i64> = ids[..5].iter().rev().copied().collect();
+            assert_eq!(leading, expected);
+        });
+    }
+
+    #[test]
+    fn reviews_schedule_due_dates() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let word_id = insert_word(&pool, "alpha").await;
+
+            let progress = increment_proficiency_for_word(
+                &pool,
+                word_id,
+                StudyEventContext::default(),
+            )
+            .await
+            .expect("Failed to increment");
+            assert_eq!(progress.interval_days, 1.0);
+            assert!(progress.due_at.is_some());
+
+            let progress = decrement_proficiency_for_word(
+                &pool,
+                word_id,
+                StudyEventContext::default(),
+            )
+            .await
+            .expect("Failed to decrement");
+            assert!(progress.interval_days < 1.0);
+        });
+    }
+
+    #[test]
+    fn grades_adjust_score_and_log_without_fuzzy_on_hard() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let word_id = insert_word(&pool, "alpha").await;
+            let progress = grade_word_with_pool(&pool, word_id, ReviewGrade::Easy, &graded())
+                .await
+                .expect("Failed to grade easy");
+            assert_eq!(progress.proficiency_score, 2);
+            assert_eq!(progress.learn_count, 1);
+
+            let progress = grade_word_with_pool(&pool, word_id, ReviewGrade::Hard, &graded())
+                .await
+                .expect("Failed to grade hard");
+            assert_eq!(progress.proficiency_score, 2);
+            assert_eq!(progress.learn_count, 2);
+            assert_eq!(fuzzy_state(&pool, word_id).await, (0, 0));
+
+            let progress = grade_word_with_pool(&pool, word_id, ReviewGrade::Again, &graded())
+                .await
+                .expect("Failed to grade again");
+            assert_eq!(progress.proficiency_score, 1);
+            assert_eq!(progress.learn_count, 2);
+            assert_eq!(fuzzy_state(&pool, word_id).await, (1, 1));
+
+            let grades: Vec<String> =
+                sqlx::query_scalar("SELECT grade FROM study_log WHERE word_id = ? ORDER BY id")
+                    .bind(word_id)
+                    .fetch_all(&pool)
+                    .await
+                    .expect("Failed to read study log");
+            assert_eq!(grades, ["easy", "hard", "again"]);
+
+            let scores: Vec<(Option<String>, Option<i64>, Option<i64>)> = sqlx::query_as(
+                "SELECT event_type, previous_score, new_score FROM study_log WHERE word_id = ? ORDER BY id",
+            )
+            .bind(word_id)
+            .fetch_all(&pool)
+            .await
+            .expect("Failed to read study log scores");
+            let grade = Some("grade".to_string());
+            assert_eq!(
+                scores,
+                [
+                    (grade.clone(), None, Some(2)),
+                    (grade.clone(), Some(2), Some(2)),
+                    (grade, Some(2), Some(1)),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn session_profile_controls_size_and_bucket_boundaries() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            assert_eq!(
+                read_session_profile(&pool).await.expect("Failed to read profile"),
+                SessionProfile::default()
+            );
+
+            let list_id = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("list-a")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+            for i in 0..30 {
+                let word_id = insert_word(&pool, &format!("word_{i}")).await;
+                map_word(&pool, list_id, word_id).await;
+                let score = if i < 10 { 8 } else { 2 };
+                sqlx::query(
+                    "INSERT INTO user_word_learning (word_id, proficiency_score, learn_count) VALUES (?, ?, 1)",
+                )
+                .bind(word_id)
+                .bind(score)
+                .execute(&pool)
+                .await
+                .expect("Failed to insert score");
+            }
+
+            let profile = SessionProfile {
+                session_size: 15,
+                new_word_quota: 0,
+                review_quota: 0,
+                low_quota: 0,
+                mid_quota: 0,
+                high_quota: 15,
+                low_score_below: 4,
+                high_score_from: 8,
+            };
+            profile.validate().expect("Profile should be valid");
+            write_session_profile(&pool, &profile)
+                .await
+                .expect("Failed to save profile");
+
+            let session = allocate_learning_session_for_list(&pool, list_id)
+                .await
+                .expect("Failed to allocate session");
+            assert_eq!(session.len(), 15);
+            let high = session
+                .iter()
+                .filter(|word| word.proficiency_score == 8)
+                .count();
+            assert_eq!(high, 10);
+
+            let invalid = SessionProfile {
+                low_score_below: 9,
+                high_score_from: 8,
+                ..SessionProfile::default()
+            };
+            assert!(invalid.validate().is_err());
+        });
+    }
+
+    #[test]
+    fn study_clock_moves_day_boundaries() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let first = insert_word(&pool, "dawn").await;
+            let second = insert_word(&pool, "morning").await;
+            for (word_id, learned_at) in [
+                (first, "2024-05-01 19:00:00"),
+                (second, "2024-05-01 23:30:00"),
+            ] {
+                sqlx::query("INSERT INTO study_log (word_id, learned_at) VALUES (?, ?)")
+                    .bind(word_id)
+                    .bind(learned_at)
+                    .execute(&pool)
+                    .await
+                    .expect("Failed to insert study log");
+            }
+
+            let days = |counts: Vec<DailyStudyCount>| {
+                counts
+                    .into_iter()
+                    .map(|count| (count.date, count.word_count))
+                    .collect::<Vec<_>>()
+            };
+            let utc = list_daily_study_counts_internal(&pool)
+                .await
+                .expect("Failed to list counts");
+            assert_eq!(days(utc), vec![("2024-05-01".to_string(), 2)]);
+
+            // 03:00 local on May 2 still belongs to May 1; 07:30 starts May 2.
+            let clock = StudyClock {
+                utc_offset_minutes: 480,
+                day_start_hour: 4,
+            };
+            clock.validate().expect("Clock should be valid");
+            write_study_clock(&pool, &clock)
+                .await
+                .expect("Failed to save clock");
+            let local = list_daily_study_counts_internal(&pool)
+                .await
+                .expect("Failed to list counts");
+            assert_eq!(
+                days(local),
+                vec![("2024-05-01".to_string(), 1), ("2024-05-02".to_string(), 1)]
+            );
+
+            let invalid = StudyClock {
+                day_start_hour: 24,
+                ..clock
+            };
+            assert!(invalid.validate().is_err());
+        });
+    }
+
+    #[test]
+    fn word_lists_report_per_list_progress() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let cet4 = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("CET-4")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+            let cet6 = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("CET-6")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+
+            let shared = insert_word(&pool, "abandon").await;
+            let mastered = insert_word(&pool, "ability").await;
+            let fresh = insert_word(&pool, "abolish").await;
+            map_word(&pool, cet4, shared).await;
+            map_word(&pool, cet4, mastered).await;
+            map_word(&pool, cet6, shared).await;
+            map_word(&pool, cet6, fresh).await;
+
+            sqlx::query(
+                "INSERT INTO user_word_learning (word_id, proficiency_score, learn_count, due_at) VALUES (?, 3, 2, datetime('now', '-1 days'))",
+            )
+            .bind(shared)
+            .execute(&pool)
+            .await
+            .expect("Failed to insert shared progress");
+            sqlx::query(
+                "INSERT INTO user_word_learning (word_id, proficiency_score, learn_count, due_at) VALUES (?, 10, 9, datetime('now', '+30 days'))",
+            )
+            .bind(mastered)
+            .execute(&pool)
+            .await
+            .expect("Failed to insert mastered progress");
+
+            let lists = list_word_lists_with_pool(&pool)
+                .await
+                .expect("Failed to list word lists");
+            let cet4_card = lists.iter().find(|list| list.id == cet4).expect("CET-4");
+            assert_eq!(cet4_card.word_count, 2);
+            assert_eq!(cet4_card.learned_count, 2);
+            assert_eq!(cet4_card.mastered_count, 1);
+            assert_eq!(cet4_card.due_count, 1);
+
+            let cet6_card = lists.iter().find(|list| list.id == cet6).expect("CET-6");
+            assert_eq!(cet6_card.word_count, 2);
+            assert_eq!(cet6_card.learned_count, 1);
+            assert_eq!(cet6_card.mastered_count, 0);
+            assert_eq!(cet6_card.due_count, 1);
+        });
+    }
+
+    #[test]
+    fn learning_sessions_persist_cursor_and_answers() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let list_id = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("list-a")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+            for i in 0..3 {
+                let word_id = insert_word(&pool, &format!("word_{i}")).await;
+                map_word(&pool, list_id, word_id).await;
+            }
+
+            let session = create_learning_session_for_list(&pool, list_id)
+                .await
+                .expect("Failed to create session");
+            assert_eq!(session.cards.len(), 3);
+            assert_eq!(session.cursor, 0);
+            let first_word = session.cards[0].word.id;
+
+            let advance = advance_learning_session_for_id(
+                &pool,
+                session.id,
+                Some(ReviewGrade::Good),
+                StudyEventContext {
+                    response_ms: Some(1800),
+                    study_mode: None,
+                },
+            )
+            .await
+            .expect("Failed to advance session");
+            assert_eq!(advance.progress.map(|progress| progress.word_id), Some(first_word));
+            let logged: (Option<i64>, Option<String>, Option<i64>) = sqlx::query_as(
+                "SELECT session_id, study_mode, response_ms FROM study_log WHERE word_id = ?",
+            )
+            .bind(first_word)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to read session log");
+            assert_eq!(logged, (Some(session.id), Some("session".to_string()), Some(1800)));
+            advance_learning_session_for_id(
+                &pool,
+                session.id,
+                None,
+                StudyEventContext::default(),
+            )
+            .await
+            .expect("Failed to skip card");
+
+            let resumed = fetch_active_learning_session(&pool)
+                .await
+                .expect("Failed to fetch session")
+                .expect("Session should still be active");
+            assert_eq!(resumed.id, session.id);
+            assert_eq!(resumed.cursor, 2);
+            assert_eq!(resumed.cards[0].grade.as_deref(), Some("good"));
+            assert!(resumed.cards[1].grade.is_none());
+            assert!(resumed.cards[1].answered_at.is_some());
+            assert!(resumed.cards[2].answered_at.is_none());
+
+            let advance = advance_learning_session_for_id(
+                &pool,
+                session.id,
+                Some(ReviewGrade::Again),
+                StudyEventContext::default(),
+            )
+            .await
+            .expect("Failed to answer last card");
+            assert_eq!(advance.session.status, "finished");
+            let last_word = session.cards[2].word.id;
+            assert_eq!(fuzzy_state(&pool, last_word).await, (1, 1));
+            assert!(fetch_active_learning_session(&pool)
+                .await
+                .expect("Failed to fetch session")
+                .is_none());
+            assert!(advance_learning_session_for_id(
+                &pool,
+                session.id,
+                None,
+                StudyEventContext::default(),
+            )
+            .await
+            .is_err());
+        });
+    }
+
+    #[test]
+    fn empty_list_keeps_the_active_session() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let list_id = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("list-a")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+            let empty_id = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("list-b")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+            let word_id = insert_word(&pool, "alpha").await;
+            map_word(&pool, list_id, word_id).await;
+
+            let session = create_learning_session_for_list(&pool, list_id)
+                .await
+                .expect("Failed to create session");
+            let error = create_learning_session_for_list(&pool, empty_id)
+                .await
+                .expect_err("Empty list should not start a session");
+            assert_eq!(error.to_string(), "词库中没有可学习的单词");
+
+            let active = fetch_active_learning_session(&pool)
+                .await
+                .expect("Failed to fetch session")
+                .expect("Previous session should stay active");
+            assert_eq!(active.id, session.id);
+        });
+    }
+
+    #[test]
+    fn undo_restores_progress_fuzzy_marks_and_logs() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let word_id = insert_word(&pool, "alpha").await;
+
+            grade_word_with_pool(&pool, word_id, ReviewGrade::Good, &graded())
+                .await
+                .expect("Failed to grade good");
+            grade_word_with_pool(&pool, word_id, ReviewGrade::Again, &graded())
+                .await
+                .expect("Failed to grade again");
+            let fuzzy_lists: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM word_list WHERE name = ?")
+                .bind(FUZZY_WORD_LIST_NAME)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count fuzzy list");
+            assert_eq!(fuzzy_lists, 1);
+
+            let summary = undo_last_grades(&pool, 1).await.expect("Failed to undo");
+            assert_eq!(summary.undone, 1);
+            assert_eq!(summary.word_ids, [word_id]);
+            let mut conn = pool.acquire().await.expect("Failed to acquire connection");
+            let progress = read_learning_progress(&mut conn, word_id)
+                .await
+                .expect("Failed to read progress");
+            drop(conn);
+            assert_eq!(progress.proficiency_score, 1);
+            assert_eq!(progress.learn_count, 1);
+            assert_eq!(progress.interval_days, 1.0);
+            let is_fuzzy: i64 =
+                sqlx::query_scalar("SELECT is_fuzzy FROM user_word_learning WHERE word_id = ?")
+                    .bind(word_id)
+                    .fetch_one(&pool)
+                    .await
+                    .expect("Failed to read fuzzy flag");
+            assert_eq!(is_fuzzy, 0);
+            let fuzzy_lists: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM word_list WHERE name = ?")
+                .bind(FUZZY_WORD_LIST_NAME)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count fuzzy list");
+            assert_eq!(fuzzy_lists, 0);
+
+            let summary = undo_last_grades(&pool, 5).await.expect("Failed to undo");
+            assert_eq!(summary.undone, 1);
+            let rows: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM user_word_learning")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count learning rows");
+            assert_eq!(rows, 0);
+            let logs: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM study_log")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count study logs");
+            assert_eq!(logs, 0);
+        });
+    }
 
+    #[test]
+    fn undo_reverts_session_answers() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let list_id = sqlx::query("INSERT INTO word_list (name) VALUES (?)")
+                .bind("list-a")
+                .execute(&pool)
+                .await
+                .expect("Failed to create list")
+                .last_insert_rowid();
+            for i in 0..2 {
+                let word_id = insert_word(&pool, &format!("word_{i}")).await;
+                map_word(&pool, list_id, word_id).await;
+            }
+            let session = create_learning_session_for_list(&pool, list_id)
+                .await
+                .expect("Failed to create session");
+
+            for _ in 0..2 {
+                advance_learning_session_for_id(
+                    &pool,
+                    session.id,
+                    Some(ReviewGrade::Good),
+                    StudyEventContext::default(),
+                )
+                .await
+                .expect("Failed to advance session");
+            }
+            undo_last_grades(&pool, 1).await.expect("Failed to undo");
+            let resumed = fetch_active_learning_session(&pool)
+                .await
+                .expect("Failed to fetch session")
+                .expect("Finished session should reopen");
+            assert_eq!(resumed.id, session.id);
+            assert_eq!(resumed.cursor, 1);
+            assert_eq!(resumed.cards[0].grade.as_deref(), Some("good"));
+            assert!(resumed.cards[1].grade.is_none());
+            assert!(resumed.cards[1].answered_at.is_none());
+
+            undo_last_grades(&pool, 1).await.expect("Failed to undo");
+            let resumed = fetch_active_learning_session(&pool)
+                .await
+                .expect("Failed to fetch session")
+                .expect("Session should stay active");
+            assert_eq!(resumed.cursor, 0);
+            assert!(resumed.cards[0].grade.is_none());
+        });
+    }
+
+    #[test]
+    fn database_state_shares_one_pool() {
+        async_runtime::block_on(async {
+            let db = Database::default();
+            assert!(db.pool().is_err());
+
+            db.set(setup_pool().await);
+            let list_id = create_word_list(&db, "list-a")
+                .await
+                .expect("Failed to create list");
+            set_active_word_list(&db, list_id)
+                .await
+                .expect("Failed to activate list");
+            let lists = list_word_lists(&db).await.expect("Failed to list word lists");
+            assert_eq!(lists.len(), 1);
+            assert!(lists[0].is_active);
+        });
+    }
+
+    #[test]
+    fn exported_csv_round_trips_through_import() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let csv = "word,phonetic,part_of_speech_and_meanings,example_sentence,example_translation,audio_uk,audio_us\n\
+apple,/ˈæp.əl/,\"n. 苹果, 苹果树\",An apple a day.,一天一苹果。,,\n\
+banana,,n. 香蕉,,,uk.mp3,us.mp3\n";
+            import_dictionary_csv_with_pool(
+                &pool,
+                "source",
+                csv,
+                false,
+                &CsvImportOptions::default(),
+            )
+            .await
+            .expect("Failed to import");
+            let source_id: i64 = sqlx::query_scalar("SELECT id FROM word_list WHERE name = 'source'")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to load list id");
+            let apple_id: i64 = sqlx::query_scalar("SELECT id FROM word WHERE word = 'apple'")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to load word id");
+            grade_word_with_pool(&pool, apple_id, ReviewGrade::Again, &graded())
+                .await
+                .expect("Failed to grade");
+
+            let exported = export_word_list_csv_with_pool(&pool, source_id, false)
+                .await
+                .expect("Failed to export");
+            assert!(exported.starts_with('\u{feff}'));
+            assert_eq!(exported.trim_start_matches('\u{feff}'), csv);
+
+            let with_progress = export_word_list_csv_with_pool(&pool, source_id, true)
+                .await
+                .expect("Failed to export progress");
+            let mut reader = csv::Reader::from_reader(
+                with_progress.trim_start_matches('\u{feff}').as_bytes(),
+            );
+            let headers = reader.headers().expect("headers").clone();
+            assert_eq!(headers.len(), REQUIRED_HEADERS.len() + PROGRESS_HEADERS.len());
+            let apple = reader
+                .records()
+                .next()
+                .expect("apple row")
+                .expect("valid row");
+            assert_eq!(&apple[0], "apple");
+            assert_eq!(&apple[7], "0");
+            assert_eq!(&apple[8], "0");
+            assert_eq!(&apple[9], "1");
+            assert!(!apple[10].is_empty());
+
+            let summary = import_dictionary_csv_with_pool(
+                &pool,
+                "copy",
+                &with_progress,
+                false,
+                &CsvImportOptions::default(),
+            )
+            .await
+            .expect("Failed to re-import");
+            assert_eq!(summary.upserted, 2);
+            let meanings: Option<String> = sqlx::query_scalar(
+                "SELECT part_of_speech_and_meanings FROM word WHERE word = 'apple'",
+            )
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to load meanings");
+            assert_eq!(meanings.as_deref(), Some("n. 苹果, 苹果树"));
+
+            assert!(export_word_list_csv_with_pool(&pool, 999, false).await.is_err());
+        });
+    }
+
+    #[test]
+    fn dictionaries_keep_their_own_definitions_of_shared_words() {
+        async_runtime::block_on(async {
+            let pool = setup_pool().await;
+            let db = Database::default();
+            db.set(pool.clone());
+            for (name, csv) in [
+                ("CET-4", "word,part_of_speech_and_meanings\napple,n. 苹果\n"),
+                (
+                    "CET-6",
+                    "word,part_of_speech_and_meanings,example_sentence\napple,n. 苹果；苹果公司,An apple a day.\n",
+                ),
+            ] {
+                import_dictionary_csv_with_pool(
+                    &pool,
+                    name,
+                    csv,
+                    false,
+                    &CsvImportOptions::default(),
+                )
+                .await
+                .expect("Failed to import");
+            }
+            let cet4_id: i64 = sqlx::query_scalar("SELECT id FROM word_list WHERE name = 'CET-4'")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to load list id");
+            let cet6_id: i64 = sqlx::query_scalar("SELECT id FROM word_list WHERE name = 'CET-6'")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to load list id");
+            let apple_id: i64 = sqlx::query_scalar("SELECT id FROM word WHERE word = 'apple'")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to load word id");
+
+            let words = fetch_words_with_condition(&pool, cet4_id, "", &[], "w.id", 10)
+                .await
+                .expect("Failed to load words");
+            assert_eq!(words[0].part_of_speech_and_meanings.as_deref(), Some("n. 苹果"));
+            assert_eq!(words[0].definition_source.as_deref(), Some("CET-4"));
+            // CET-4 has no example, so CET-6's must not leak into its card.
+            assert!(words[0].example_sentence.is_none());
+            let exported = export_word_list_csv_with_pool(&pool, cet4_id, false)
+                .await
+                .expect("Failed to export");
+            assert!(exported.contains("apple,,n. 苹果,,,,"));
+
+            let definitions = list_word_definitions_with_pool(&pool, apple_id)
+                .await
+                .expect("Failed to list definitions");
+            assert_eq!(definitions.len(), 2);
+            let cet6 = definitions
+                .iter()
+                .find(|definition| definition.word_list_id == cet6_id)
+                .expect("CET-6 definition");
+            assert!(cet6.is_displayed);
+            assert_eq!(cet6.source_kind, "csv");
+            assert!(definitions
+                .iter()
+                .any(|definition| definition.word_list_name == "CET-4" && !definition.is_displayed));
+
+            delete_word_list(&db, cet6_id)
+                .await
+                .expect("Failed to delete list");
+            let meanings: Option<String> = sqlx::query_scalar(
+                "SELECT part_of_speech_and_meanings FROM word WHERE id = ?",
+            )
+            .bind(apple_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to load meanings");
+            assert_eq!(meanings.as_deref(), Some("n. 苹果"));
+            let definitions = list_word_definitions_with_pool(&pool, apple_id)
+                .await
+                .expect("Failed to list definitions");
+            assert_eq!(definitions.len(), 1);
+            assert!(definitions[0].is_displayed);
+            // With one dictionary left there is no source worth naming.
+            let words = fetch_words_with_condition(&pool, cet4_id, "", &[], "w.id", 10)
+                .await
+                .expect("Failed to load words");
+            assert!(words[0].definition_source.is_none());
+            let imports: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM dictionary_import")
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count imports");
+            assert_eq!(imports, 1);
+        });
+    }
+}